@@ -0,0 +1,163 @@
+use crate::cli::ConnectionArgs;
+use crate::config::{self, LoadedConfig};
+use crate::errors::AppError;
+use crate::mcp_client::{CapabilityKind, McpClient};
+use futures::future::join_all;
+use serde_json::{Map, Value, json};
+use std::future::Future;
+use std::path::PathBuf;
+
+/// Runs `run` against every configured server concurrently and merges the `key` arrays of
+/// the results, prefixing each item name with `<server>:`. When `capability` is given it
+/// is checked first.
+///
+/// A server that fails is reported under `errors` instead of failing the command. For name
+/// lookups (`name` set) servers without a match are skipped, and the command fails with
+/// `not_found` only when no server has one. `sources` names the config file of each server.
+pub async fn across_servers<F, Fut>(
+    connection: &ConnectionArgs,
+    config_path: Option<PathBuf>,
+    capability: Option<CapabilityKind>,
+    key: &str,
+    name: Option<&str>,
+    run: F,
+) -> Result<Value, AppError>
+where
+    F: Fn(McpClient) -> Fut,
+    Fut: Future<Output = Result<Value, AppError>>,
+{
+    let loaded = LoadedConfig::load(config_path.clone())?;
+    if loaded.servers.is_empty() {
+        return Err(AppError::new("config_missing", "No servers configured"));
+    }
+
+    let runs = loaded.servers.keys().map(|server| {
+        let mut connection = connection.clone();
+        connection.server = Some(server.clone());
+        let config_path = config_path.clone();
+        let run = &run;
+        async move {
+            let result = async {
+                let resolved = config::resolve_connection(&connection, config_path).await?;
+                let client = McpClient::new(resolved);
+                if let Some(capability) = capability {
+                    client.ensure_capability(capability).await?;
+                }
+                run(client).await
+            };
+            (server.clone(), result.await)
+        }
+    });
+    let results = join_all(runs).await;
+
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut truncated = false;
+    for (server, result) in results {
+        match result {
+            Ok(Value::Object(mut output)) => {
+                // Cursors cannot be merged, so a server with pages left makes the listing partial.
+                truncated |= output.get("truncated") == Some(&Value::Bool(true))
+                    || output
+                        .get("next_cursor")
+                        .is_some_and(|cursor| !cursor.is_null());
+                if let Some(Value::Array(found)) = output.remove(key) {
+                    items.extend(found.into_iter().map(|item| namespaced(&server, item)));
+                }
+            }
+            Ok(_) => {}
+            Err(err) if name.is_some() && err.code() == "not_found" => {}
+            Err(err) => errors.push(json!({
+                "server": server,
+                "code": err.code(),
+                "message": err.message(),
+            })),
+        }
+    }
+
+    if let Some(name) = name
+        && items.is_empty()
+        && errors.is_empty()
+    {
+        return Err(AppError::new(
+            "not_found",
+            format!("'{name}' not found on any server"),
+        ));
+    }
+    let sources = loaded
+        .servers
+        .iter()
+        .map(|(server, sourced)| (server.clone(), json!(sourced.source)))
+        .collect();
+    let mut output = Map::new();
+    output.insert(key.to_string(), Value::Array(items));
+    output.insert("sources".to_string(), Value::Object(sources));
+    if truncated {
+        output.insert("truncated".to_string(), Value::Bool(true));
+    }
+    if !errors.is_empty() {
+        output.insert("errors".to_string(), Value::Array(errors));
+    }
+    Ok(Value::Object(output))
+}
+
+/// Keeps the first `limit` merged `key` items, marking the output `truncated` when some were
+/// left out.
+pub fn keep_first(output: &mut Value, key: &str, limit: usize) {
+    if let Some(Value::Array(items)) = output.get_mut(key)
+        && items.len() > limit
+    {
+        items.truncate(limit);
+        output["truncated"] = Value::Bool(true);
+    }
+}
+
+/// Sorts the merged `key` items by descending `score` and keeps the first `limit`.
+pub fn keep_top_scored(output: &mut Value, key: &str, limit: usize) {
+    if let Some(Value::Array(items)) = output.get_mut(key) {
+        let score = |item: &Value| item.get("score").and_then(Value::as_u64).unwrap_or(0);
+        items.sort_by_key(|item| std::cmp::Reverse(score(item)));
+        items.truncate(limit);
+    }
+}
+
+fn namespaced(server: &str, mut item: Value) -> Value {
+    if let Some(name) = item.get("name").and_then(Value::as_str) {
+        item["name"] = Value::String(format!("{server}:{name}"));
+    }
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_top_scored() {
+        let mut output = json!({"tools": [
+            namespaced("a", json!({"name": "echo", "score": 5})),
+            namespaced("b", json!({"name": "echo", "score": 30})),
+            namespaced("c", json!({"name": "add", "score": 1})),
+        ]});
+        keep_top_scored(&mut output, "tools", 2);
+        assert_eq!(
+            output,
+            json!({"tools": [
+                {"name": "b:echo", "score": 30},
+                {"name": "a:echo", "score": 5},
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_keep_first() {
+        let mut output = json!({"tools": [{"name": "a:echo"}, {"name": "b:add"}]});
+        keep_first(&mut output, "tools", 2);
+        assert_eq!(output.get("truncated"), None);
+        keep_first(&mut output, "tools", 1);
+        assert_eq!(
+            output,
+            json!({"tools": [{"name": "a:echo"}], "truncated": true})
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::errors::AppError;
+use reqwest::Url;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CALLBACK_PATH: &str = "/callback";
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Localhost listener that receives the authorization redirect.
+pub(crate) struct CallbackListener {
+    listener: TcpListener,
+    redirect_uri: String,
+}
+
+impl CallbackListener {
+    /// Binds `127.0.0.1:<port>`; port `0` picks a free port.
+    pub(crate) async fn bind(port: u16) -> Result<Self, AppError> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|err| {
+                AppError::new(
+                    "auth_callback",
+                    format!("Failed to bind redirect listener: {err}"),
+                )
+            })?;
+        let port = listener
+            .local_addr()
+            .map_err(|err| {
+                AppError::new(
+                    "auth_callback",
+                    format!("Failed to bind redirect listener: {err}"),
+                )
+            })?
+            .port();
+
+        Ok(Self {
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{port}{CALLBACK_PATH}"),
+        })
+    }
+
+    pub(crate) fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Waits for the redirect and returns the authorization code after checking `state`.
+    pub(crate) async fn wait_for_code(self, state: &str) -> Result<String, AppError> {
+        tokio::time::timeout(CALLBACK_TIMEOUT, self.accept_callback(state))
+            .await
+            .map_err(|_| {
+                AppError::new(
+                    "auth_timeout",
+                    "Timed out waiting for the authorization redirect",
+                )
+            })?
+    }
+
+    async fn accept_callback(&self, state: &str) -> Result<String, AppError> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await.map_err(|err| {
+                AppError::new("auth_callback", format!("Redirect listener failed: {err}"))
+            })?;
+
+            let Some(target) = read_request_target(&mut stream).await else {
+                continue;
+            };
+            let Ok(url) = Url::parse(&format!("http://127.0.0.1{target}")) else {
+                respond(&mut stream, "400 Bad Request", "Invalid request").await;
+                continue;
+            };
+            if url.path() != CALLBACK_PATH {
+                respond(&mut stream, "404 Not Found", "Not found").await;
+                continue;
+            }
+
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            };
+
+            if let Some(error) = param("error") {
+                respond(
+                    &mut stream,
+                    "200 OK",
+                    "Authorization failed. You can close this window.",
+                )
+                .await;
+                let message = match param("error_description") {
+                    Some(description) => format!("{error}: {description}"),
+                    None => error,
+                };
+                return Err(AppError::new(
+                    "auth_denied",
+                    format!("Authorization failed: {message}"),
+                ));
+            }
+
+            if param("state").as_deref() != Some(state) {
+                respond(&mut stream, "400 Bad Request", "State mismatch").await;
+                return Err(AppError::new(
+                    "auth_callback",
+                    "Authorization redirect state mismatch",
+                ));
+            }
+
+            let Some(code) = param("code") else {
+                respond(&mut stream, "400 Bad Request", "Missing code").await;
+                return Err(AppError::new(
+                    "auth_callback",
+                    "Authorization redirect is missing the code parameter",
+                ));
+            };
+
+            respond(
+                &mut stream,
+                "200 OK",
+                "Authorization complete. You can close this window.",
+            )
+            .await;
+            return Ok(code);
+        }
+    }
+}
+
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 || buffer.len() > 16 * 1024 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&buffer);
+    let mut parts = request.lines().next()?.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
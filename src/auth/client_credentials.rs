@@ -0,0 +1,203 @@
+use crate::config::{OAuthConfig, ResolvedConnection};
+use crate::errors::AppError;
+
+use super::store::{StoredToken, TokenStore, now_unix};
+use super::token::request_token;
+use super::{auth_http_client, server_url};
+
+/// Cached tokens this close to expiry are replaced rather than reused.
+const EXPIRY_MARGIN_SECS: u64 = 30;
+
+/// Returns a client-credentials access token for the connection's server.
+///
+/// A cached token from the token store is reused while it is valid, unless `force_refresh`
+/// is set (after the server rejected it).
+pub(crate) async fn client_credentials_token(
+    connection: &ResolvedConnection,
+    oauth: &OAuthConfig,
+    force_refresh: bool,
+) -> Result<String, AppError> {
+    let url = server_url(connection)?;
+    let store = TokenStore::open().ok();
+
+    if !force_refresh
+        && let Some(store) = &store
+        && let Some(token) = store.get(url)?
+        && is_reusable(&token, oauth, now_unix())
+    {
+        return Ok(token.access_token);
+    }
+
+    let client_secret = oauth
+        .client_secret_env
+        .as_ref()
+        .map(|name| {
+            connection
+                .env_var(name)
+                .ok_or_else(|| AppError::new("token_missing", format!("Token env not set: {name}")))
+        })
+        .transpose()?;
+
+    let client = auth_http_client(connection)?;
+    let token = fetch_token(&client, oauth, client_secret.as_deref(), url).await?;
+    let access_token = token.access_token.clone();
+    if let Some(store) = &store {
+        store.put(url, token)?;
+    }
+    Ok(access_token)
+}
+
+async fn fetch_token(
+    client: &reqwest::Client,
+    oauth: &OAuthConfig,
+    client_secret: Option<&str>,
+    resource: &str,
+) -> Result<StoredToken, AppError> {
+    let scope = oauth.scopes.join(" ");
+    let mut form = vec![
+        ("grant_type", "client_credentials"),
+        ("client_id", oauth.client_id.as_str()),
+        ("resource", resource),
+    ];
+    if let Some(secret) = client_secret {
+        form.push(("client_secret", secret));
+    }
+    if !scope.is_empty() {
+        form.push(("scope", scope.as_str()));
+    }
+
+    let response = request_token(client, &oauth.token_url, &form).await?;
+    Ok(response.into_stored(&oauth.token_url, &oauth.client_id, None, None))
+}
+
+fn is_reusable(token: &StoredToken, oauth: &OAuthConfig, now: u64) -> bool {
+    token.token_endpoint == oauth.token_url
+        && token.client_id == oauth.client_id
+        && token
+            .expires_at
+            .is_none_or(|expires_at| expires_at > now + EXPIRY_MARGIN_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn oauth(token_url: &str) -> OAuthConfig {
+        OAuthConfig {
+            token_url: token_url.to_string(),
+            client_id: "ci-agent".to_string(),
+            client_secret_env: None,
+            scopes: vec!["mcp:read".to_string(), "mcp:write".to_string()],
+        }
+    }
+
+    /// Serves one HTTP response and returns the raw request it received.
+    async fn stub_token_endpoint(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token() {
+        let (url, request) = stub_token_endpoint(
+            "200 OK",
+            r#"{"access_token":"cc-token","token_type":"Bearer","expires_in":600}"#,
+        )
+        .await;
+
+        let token = fetch_token(
+            &reqwest::Client::new(),
+            &oauth(&url),
+            Some("s3cret"),
+            "https://mcp.example.com/mcp",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(token.access_token, "cc-token");
+        assert_eq!(token.token_endpoint, url);
+        assert_eq!(token.client_id, "ci-agent");
+        assert!(token.client_secret.is_none());
+        assert!(token.expires_at.unwrap() >= now_unix() + 590);
+
+        let request = request.await.unwrap();
+        assert!(request.contains("grant_type=client_credentials"));
+        assert!(request.contains("client_id=ci-agent"));
+        assert!(request.contains("client_secret=s3cret"));
+        assert!(request.contains("scope=mcp%3Aread+mcp%3Awrite"));
+        assert!(request.contains("resource=https%3A%2F%2Fmcp.example.com%2Fmcp"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_error() {
+        let (url, _request) = stub_token_endpoint(
+            "401 Unauthorized",
+            r#"{"error":"invalid_client","error_description":"bad secret"}"#,
+        )
+        .await;
+
+        let err = fetch_token(&reqwest::Client::new(), &oauth(&url), None, "https://x")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "auth_token");
+        assert!(err.message().contains("invalid_client: bad secret"));
+    }
+
+    #[test]
+    fn test_is_reusable() {
+        let config = oauth("https://auth.example.com/token");
+        let token = StoredToken {
+            access_token: "cc".to_string(),
+            refresh_token: None,
+            expires_at: Some(1_000),
+            scope: None,
+            token_endpoint: config.token_url.clone(),
+            client_id: config.client_id.clone(),
+            client_secret: None,
+        };
+
+        assert!(is_reusable(&token, &config, 900));
+        assert!(!is_reusable(&token, &config, 980));
+
+        let other_client = OAuthConfig {
+            client_id: "other".to_string(),
+            ..config.clone()
+        };
+        assert!(!is_reusable(&token, &other_client, 900));
+    }
+}
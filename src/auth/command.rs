@@ -0,0 +1,194 @@
+use crate::config::{CommandSpec, TokenCommand};
+use crate::errors::AppError;
+use crate::paths::user_config_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use super::store::{now_unix, write_cache};
+
+const CACHE_FILE: &str = "token_command_cache.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedToken {
+    token: String,
+    /// Unix timestamp (seconds) after which the cached output must be regenerated.
+    expires_at: u64,
+}
+
+/// Returns the bearer token printed by the command, reusing a cached value within its TTL.
+///
+/// `force_refresh` skips the cache, e.g. after the server rejected the cached token. The
+/// command runs with `env` instead of the inherited environment when given.
+pub async fn command_token(
+    command: &TokenCommand,
+    env: Option<&BTreeMap<String, String>>,
+    force_refresh: bool,
+) -> Result<String, AppError> {
+    let cache = command
+        .ttl
+        .and_then(|_| user_config_dir())
+        .map(|dir| dir.join(CACHE_FILE));
+    let key = cache_key(&command.command);
+
+    if !force_refresh
+        && let Some(path) = &cache
+        && let Some(cached) = read_cache(path).remove(&key)
+        && cached.expires_at > now_unix()
+    {
+        return Ok(cached.token);
+    }
+
+    let token = run_command(&command.command, env).await?;
+
+    if let (Some(path), Some(ttl)) = (&cache, command.ttl) {
+        let mut entries = read_cache(path);
+        let now = now_unix();
+        entries.retain(|_, cached| cached.expires_at > now);
+        entries.insert(
+            key,
+            CachedToken {
+                token: token.clone(),
+                expires_at: now + ttl,
+            },
+        );
+        write_cache(path, &entries);
+    }
+    Ok(token)
+}
+
+async fn run_command(
+    spec: &CommandSpec,
+    env: Option<&BTreeMap<String, String>>,
+) -> Result<String, AppError> {
+    let mut command = match spec {
+        CommandSpec::Shell(line) if cfg!(windows) => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", line]);
+            command
+        }
+        CommandSpec::Shell(line) => {
+            let mut command = Command::new("sh");
+            command.args(["-c", line]);
+            command
+        }
+        CommandSpec::Argv(argv) => {
+            let (program, args) = argv
+                .split_first()
+                .ok_or_else(|| AppError::new("token_command", "token_command is empty"))?;
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+    };
+
+    if let Some(env) = env {
+        command.env_clear().envs(env);
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| {
+            AppError::new(
+                "token_command",
+                format!("Failed to run token command `{spec}`: {err}"),
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::new(
+            "token_command",
+            format!(
+                "Token command `{spec}` failed ({}): {}",
+                output.status,
+                stderr.trim()
+            ),
+        ));
+    }
+
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(AppError::new(
+            "token_command",
+            format!("Token command `{spec}` printed no token"),
+        ));
+    }
+    Ok(token)
+}
+
+/// Cache entries are keyed by a hash of the command so the file never stores command lines.
+fn cache_key(spec: &CommandSpec) -> String {
+    let raw = match spec {
+        CommandSpec::Shell(line) => format!("sh:{line}"),
+        CommandSpec::Argv(argv) => format!("argv:{}", argv.join("\0")),
+    };
+    Sha256::digest(raw.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn read_cache(path: &Path) -> BTreeMap<String, CachedToken> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_command() {
+        let token = run_command(&CommandSpec::Shell("echo ' abc123 '".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(token, "abc123");
+
+        let err = run_command(&CommandSpec::Shell("exit 3".to_string()), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "token_command");
+
+        let err = run_command(&CommandSpec::Argv(Vec::new()), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "token_command");
+
+        let env = BTreeMap::from([
+            (
+                "PATH".to_string(),
+                std::env::var("PATH").unwrap_or_default(),
+            ),
+            ("CALL_MCP_TEST_TOKEN".to_string(), "from-env".to_string()),
+        ]);
+        let token = run_command(
+            &CommandSpec::Shell("echo $CALL_MCP_TEST_TOKEN".to_string()),
+            Some(&env),
+        )
+        .await
+        .unwrap();
+        assert_eq!(token, "from-env");
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let shell = cache_key(&CommandSpec::Shell("gh auth token".to_string()));
+        let argv = cache_key(&CommandSpec::Argv(vec![
+            "gh".to_string(),
+            "auth".to_string(),
+            "token".to_string(),
+        ]));
+        assert_eq!(shell.len(), 64);
+        assert_ne!(shell, argv);
+    }
+}
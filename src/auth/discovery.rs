@@ -0,0 +1,195 @@
+use crate::errors::AppError;
+use reqwest::Url;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct AuthServerMetadata {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+}
+
+/// Result of MCP authorization discovery for one server.
+#[derive(Debug, Clone)]
+pub(crate) struct Discovery {
+    pub metadata: AuthServerMetadata,
+    /// Scopes advertised by the protected resource, falling back to the authorization server.
+    pub scopes: Vec<String>,
+}
+
+/// Discovers the authorization server for `server_url`.
+///
+/// Follows the MCP authorization spec: protected resource metadata (RFC 9728) first,
+/// then authorization server metadata (RFC 8414 / OpenID discovery). Servers that
+/// publish neither get the spec's default `/authorize`, `/token` and `/register` endpoints.
+pub(crate) async fn discover(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<Discovery, AppError> {
+    let server_url = parse_url(server_url)?;
+
+    let mut resource = None;
+    for candidate in protected_resource_candidates(&server_url) {
+        if let Some(metadata) = fetch_json::<ProtectedResourceMetadata>(client, candidate).await {
+            resource = Some(metadata);
+            break;
+        }
+    }
+
+    let issuer = match resource
+        .as_ref()
+        .and_then(|metadata| metadata.authorization_servers.first())
+    {
+        Some(issuer) => parse_url(issuer)?,
+        None => origin(&server_url),
+    };
+
+    let mut metadata = None;
+    for candidate in authorization_server_candidates(&issuer) {
+        if let Some(found) = fetch_json::<AuthServerMetadata>(client, candidate).await {
+            metadata = Some(found);
+            break;
+        }
+    }
+
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None if resource.is_none() => default_metadata(&issuer),
+        None => {
+            return Err(AppError::new(
+                "auth_discovery",
+                format!("No authorization server metadata found for {issuer}"),
+            ));
+        }
+    };
+
+    let scopes = match resource {
+        Some(resource) if !resource.scopes_supported.is_empty() => resource.scopes_supported,
+        _ => metadata.scopes_supported.clone(),
+    };
+
+    Ok(Discovery { metadata, scopes })
+}
+
+fn protected_resource_candidates(server_url: &Url) -> Vec<Url> {
+    well_known_candidates(server_url, "oauth-protected-resource")
+}
+
+fn authorization_server_candidates(issuer: &Url) -> Vec<Url> {
+    let mut candidates = well_known_candidates(issuer, "oauth-authorization-server");
+    candidates.extend(well_known_candidates(issuer, "openid-configuration"));
+
+    let path = issuer.path().trim_end_matches('/');
+    if !path.is_empty() {
+        let mut url = origin(issuer);
+        url.set_path(&format!("{path}/.well-known/openid-configuration"));
+        candidates.push(url);
+    }
+    candidates
+}
+
+/// Path-aware well-known URLs: `/.well-known/<suffix>/<path>` then `/.well-known/<suffix>`.
+fn well_known_candidates(url: &Url, suffix: &str) -> Vec<Url> {
+    let mut candidates = Vec::new();
+    let path = url.path().trim_end_matches('/');
+    if !path.is_empty() {
+        let mut candidate = origin(url);
+        candidate.set_path(&format!("/.well-known/{suffix}{path}"));
+        candidates.push(candidate);
+    }
+    let mut candidate = origin(url);
+    candidate.set_path(&format!("/.well-known/{suffix}"));
+    candidates.push(candidate);
+    candidates
+}
+
+fn default_metadata(issuer: &Url) -> AuthServerMetadata {
+    let endpoint = |path: &str| {
+        let mut url = origin(issuer);
+        url.set_path(path);
+        url.to_string()
+    };
+    AuthServerMetadata {
+        authorization_endpoint: endpoint("/authorize"),
+        token_endpoint: endpoint("/token"),
+        registration_endpoint: Some(endpoint("/register")),
+        scopes_supported: Vec::new(),
+    }
+}
+
+fn origin(url: &Url) -> Url {
+    let mut origin = url.clone();
+    origin.set_path("/");
+    origin.set_query(None);
+    origin.set_fragment(None);
+    origin
+}
+
+fn parse_url(raw: &str) -> Result<Url, AppError> {
+    Url::parse(raw).map_err(|err| AppError::new("invalid_url", format!("Invalid URL {raw}: {err}")))
+}
+
+async fn fetch_json<T: DeserializeOwned>(client: &reqwest::Client, url: Url) -> Option<T> {
+    let response = client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(candidates: Vec<Url>) -> Vec<String> {
+        candidates.into_iter().map(|url| url.to_string()).collect()
+    }
+
+    #[test]
+    fn test_protected_resource_candidates() {
+        let url = Url::parse("https://example.com/v1/mcp?x=1").unwrap();
+        assert_eq!(
+            urls(protected_resource_candidates(&url)),
+            vec![
+                "https://example.com/.well-known/oauth-protected-resource/v1/mcp",
+                "https://example.com/.well-known/oauth-protected-resource",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_authorization_server_candidates() {
+        let root = Url::parse("https://auth.example.com").unwrap();
+        assert_eq!(
+            urls(authorization_server_candidates(&root)),
+            vec![
+                "https://auth.example.com/.well-known/oauth-authorization-server",
+                "https://auth.example.com/.well-known/openid-configuration",
+            ]
+        );
+
+        let tenant = Url::parse("https://auth.example.com/tenant1").unwrap();
+        assert_eq!(
+            urls(authorization_server_candidates(&tenant)),
+            vec![
+                "https://auth.example.com/.well-known/oauth-authorization-server/tenant1",
+                "https://auth.example.com/.well-known/oauth-authorization-server",
+                "https://auth.example.com/.well-known/openid-configuration/tenant1",
+                "https://auth.example.com/.well-known/openid-configuration",
+                "https://auth.example.com/tenant1/.well-known/openid-configuration",
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::config::ResolvedConnection;
+use crate::errors::AppError;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::callback::CallbackListener;
+use super::discovery::{AuthServerMetadata, discover};
+use super::pkce::{Pkce, random_token};
+use super::store::TokenStore;
+use super::token::request_token;
+use super::{auth_http_client, server_url};
+
+const CLIENT_NAME: &str = "call-mcp";
+
+/// Options for the interactive authorization-code login.
+#[derive(Debug, Clone, Default)]
+pub struct LoginOptions {
+    /// Pre-registered client ID; dynamic client registration is used when absent.
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub scopes: Vec<String>,
+    pub redirect_port: u16,
+    pub open_browser: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistrationResponse {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+/// Runs the OAuth 2.1 authorization-code flow with PKCE and stores the resulting tokens.
+pub async fn login(
+    connection: &ResolvedConnection,
+    options: LoginOptions,
+) -> Result<Value, AppError> {
+    let url = server_url(connection)?;
+    let client = auth_http_client(connection)?;
+    let discovery = discover(&client, url).await?;
+    let metadata = &discovery.metadata;
+
+    let listener = CallbackListener::bind(options.redirect_port).await?;
+    let redirect_uri = listener.redirect_uri().to_string();
+
+    let (client_id, client_secret) = match options.client_id {
+        Some(client_id) => (client_id, options.client_secret),
+        None => register_client(&client, metadata, &redirect_uri).await?,
+    };
+
+    let scopes = if options.scopes.is_empty() {
+        discovery.scopes.clone()
+    } else {
+        options.scopes
+    };
+    let scope = scopes.join(" ");
+
+    let pkce = Pkce::generate();
+    let state = random_token(16);
+    let mut params = vec![
+        ("response_type", "code"),
+        ("client_id", client_id.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("code_challenge", pkce.challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        ("state", state.as_str()),
+        ("resource", url),
+    ];
+    if !scope.is_empty() {
+        params.push(("scope", scope.as_str()));
+    }
+    let authorize_url =
+        Url::parse_with_params(&metadata.authorization_endpoint, &params).map_err(|err| {
+            AppError::new(
+                "auth_discovery",
+                format!(
+                    "Invalid authorization endpoint {}: {err}",
+                    metadata.authorization_endpoint
+                ),
+            )
+        })?;
+
+    eprintln!("Open this URL in a browser to authorize call-mcp:\n\n  {authorize_url}\n");
+    if options.open_browser {
+        open_browser(authorize_url.as_str());
+    }
+
+    let code = listener.wait_for_code(&state).await?;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", client_id.as_str()),
+        ("code_verifier", pkce.verifier.as_str()),
+        ("resource", url),
+    ];
+    if let Some(secret) = &client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let response = request_token(&client, &metadata.token_endpoint, &form).await?;
+    let token = response.into_stored(&metadata.token_endpoint, &client_id, client_secret, None);
+
+    let store = TokenStore::open()?;
+    let result = json!({
+        "url": url,
+        "token_file": store.path().display().to_string(),
+        "scope": token.scope,
+        "expires_at": token.expires_at,
+        "refreshable": token.refresh_token.is_some(),
+    });
+    store.put(url, token)?;
+    Ok(result)
+}
+
+/// Removes stored tokens for the connection's server URL.
+pub fn logout(connection: &ResolvedConnection) -> Result<Value, AppError> {
+    let url = server_url(connection)?;
+    let store = TokenStore::open()?;
+    let removed = store.remove(url)?;
+    Ok(json!({
+        "url": url,
+        "removed": removed,
+    }))
+}
+
+async fn register_client(
+    client: &reqwest::Client,
+    metadata: &AuthServerMetadata,
+    redirect_uri: &str,
+) -> Result<(String, Option<String>), AppError> {
+    let endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+        AppError::new(
+            "auth_registration",
+            "Authorization server does not support dynamic client registration; pass --client-id",
+        )
+    })?;
+
+    let response = client
+        .post(endpoint)
+        .json(&json!({
+            "client_name": CLIENT_NAME,
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        }))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| {
+            AppError::new(
+                "auth_registration",
+                format!("Client registration failed: {err}"),
+            )
+        })?;
+
+    let registration: RegistrationResponse = response.json().await.map_err(|err| {
+        AppError::new(
+            "auth_registration",
+            format!("Invalid client registration response: {err}"),
+        )
+    })?;
+    Ok((registration.client_id, registration.client_secret))
+}
+
+fn open_browser(url: &str) {
+    let result = if cfg!(windows) {
+        std::process::Command::new("rundll32")
+            .args(["url.dll,FileProtocolHandler", url])
+            .spawn()
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else {
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+
+    if result.is_err() {
+        eprintln!("Could not open a browser automatically; open the URL above manually.");
+    }
+}
@@ -0,0 +1,75 @@
+mod callback;
+mod client_credentials;
+mod command;
+mod discovery;
+mod login;
+mod pkce;
+mod refresh;
+mod store;
+mod token;
+
+use crate::config::{ResolvedConnection, header_exists};
+use crate::errors::AppError;
+use crate::types::Header;
+use reqwest::header::HeaderMap;
+
+use client_credentials::client_credentials_token;
+
+pub use command::command_token;
+pub use login::{LoginOptions, login, logout};
+pub use refresh::refresh_access_token;
+pub use store::TokenStore;
+pub(crate) use store::{now_unix, write_cache};
+
+/// Attaches an OAuth bearer token when no Authorization header is configured.
+///
+/// Servers with an `oauth` block get a (cached) client-credentials token; others use
+/// the token saved by `auth login`, if any.
+pub async fn authorize(connection: &mut ResolvedConnection) -> Result<(), AppError> {
+    if header_exists(&connection.headers, "Authorization") {
+        return Ok(());
+    }
+    let Some(url) = connection.transport.url() else {
+        return Ok(());
+    };
+
+    let token = match &connection.oauth {
+        Some(oauth) => Some(client_credentials_token(connection, oauth, false).await?),
+        None => stored_access_token(url)?,
+    };
+    if let Some(token) = token {
+        connection
+            .headers
+            .push(Header::new("Authorization", format!("Bearer {token}")));
+        connection.refreshable_token = true;
+    }
+    Ok(())
+}
+
+/// Returns the stored OAuth access token for `url`, if one exists.
+fn stored_access_token(url: &str) -> Result<Option<String>, AppError> {
+    let Ok(store) = TokenStore::open() else {
+        return Ok(None);
+    };
+    Ok(store.get(url)?.map(|token| token.access_token))
+}
+
+pub(crate) fn server_url(connection: &ResolvedConnection) -> Result<&str, AppError> {
+    connection.transport.url().ok_or_else(|| {
+        AppError::new(
+            "auth_unsupported",
+            "OAuth login is only available for HTTP servers",
+        )
+    })
+}
+
+pub(crate) fn auth_http_client(
+    connection: &ResolvedConnection,
+) -> Result<reqwest::Client, AppError> {
+    crate::mcp_client::build_http_client(
+        &HeaderMap::new(),
+        connection.timeout,
+        connection.connect_timeout,
+        &connection.user_agent,
+    )
+}
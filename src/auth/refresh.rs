@@ -0,0 +1,68 @@
+use crate::config::ResolvedConnection;
+use crate::errors::AppError;
+
+use super::client_credentials::client_credentials_token;
+use super::command::command_token;
+use super::store::TokenStore;
+use super::token::request_token;
+use super::{auth_http_client, server_url};
+
+/// Replaces a rejected access token: re-runs the token command, requests a new
+/// client-credentials token, or exchanges the stored refresh token.
+pub async fn refresh_access_token(connection: &ResolvedConnection) -> Result<String, AppError> {
+    if let Some(command) = &connection.token_command {
+        return command_token(command, connection.env.as_ref(), true).await;
+    }
+    if let Some(oauth) = &connection.oauth {
+        return client_credentials_token(connection, oauth, true).await;
+    }
+
+    let url = server_url(connection)?;
+    let store = TokenStore::open()?;
+    let token = store.get(url)?.ok_or_else(|| {
+        AppError::new(
+            "auth_required",
+            format!("No stored token for {url}; run `call-mcp auth login`"),
+        )
+    })?;
+    let refresh_token = token.refresh_token.clone().ok_or_else(|| {
+        AppError::new(
+            "auth_required",
+            format!(
+                "Token for {url} was rejected and cannot be refreshed; run `call-mcp auth login`"
+            ),
+        )
+    })?;
+
+    let client = auth_http_client(connection)?;
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", token.client_id.as_str()),
+        ("resource", url),
+    ];
+    if let Some(secret) = &token.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let response = request_token(&client, &token.token_endpoint, &form)
+        .await
+        .map_err(|err| {
+            AppError::new(
+                "auth_required",
+                format!(
+                    "Token refresh failed ({}); run `call-mcp auth login`",
+                    err.message()
+                ),
+            )
+        })?;
+
+    let refreshed = response.into_stored(
+        &token.token_endpoint,
+        &token.client_id,
+        token.client_secret,
+        Some(refresh_token),
+    );
+    let access_token = refreshed.access_token.clone();
+    store.put(url, refreshed)?;
+    Ok(access_token)
+}
@@ -0,0 +1,178 @@
+use crate::errors::AppError;
+use crate::paths::user_config_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const TOKEN_FILE_ENV: &str = "CALL_MCP_TOKEN_FILE";
+
+/// OAuth tokens obtained for one MCP server, keyed by server URL in the token file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) after which the access token is no longer valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    pub token_endpoint: String,
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenFile {
+    #[serde(default)]
+    servers: BTreeMap<String, StoredToken>,
+}
+
+/// Per-user token file shared by every call-mcp invocation.
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl TokenStore {
+    /// Opens the default token file (`$CALL_MCP_TOKEN_FILE` or `<user config dir>/tokens.json`).
+    pub fn open() -> Result<Self, AppError> {
+        let path = match std::env::var_os(TOKEN_FILE_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => user_config_dir()
+                .ok_or_else(|| {
+                    AppError::new("token_store", "Cannot determine user config directory")
+                })?
+                .join("tokens.json"),
+        };
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, url: &str) -> Result<Option<StoredToken>, AppError> {
+        Ok(self.read()?.servers.remove(url))
+    }
+
+    pub fn put(&self, url: &str, token: StoredToken) -> Result<(), AppError> {
+        let mut file = self.read()?;
+        file.servers.insert(url.to_string(), token);
+        self.write(&file)
+    }
+
+    pub fn remove(&self, url: &str) -> Result<bool, AppError> {
+        let mut file = self.read()?;
+        let removed = file.servers.remove(url).is_some();
+        if removed {
+            self.write(&file)?;
+        }
+        Ok(removed)
+    }
+
+    fn read(&self) -> Result<TokenFile, AppError> {
+        if !self.path.exists() {
+            return Ok(TokenFile::default());
+        }
+
+        let contents = std::fs::read_to_string(&self.path).map_err(|err| {
+            AppError::new(
+                "token_store",
+                format!("Failed to read token file: {} ({err})", self.path.display()),
+            )
+        })?;
+        serde_json::from_str(&contents).map_err(|err| {
+            AppError::new(
+                "token_store",
+                format!(
+                    "Failed to parse token file: {} ({err})",
+                    self.path.display()
+                ),
+            )
+        })
+    }
+
+    fn write(&self, file: &TokenFile) -> Result<(), AppError> {
+        write_private_json(&self.path, file)
+    }
+}
+
+/// Writes `value` as JSON readable only by the current user (on Unix).
+///
+/// The file is created with those permissions under a temporary name and renamed into
+/// place, so its contents are never readable by others or half-written.
+pub(crate) fn write_private_json<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    let map_err = |err: std::io::Error| {
+        AppError::new(
+            "token_store",
+            format!("Failed to write token file: {} ({err})", path.display()),
+        )
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(map_err)?;
+    }
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|err| AppError::new("json_encode", format!("Failed to encode tokens: {err}")))?;
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+    // A leftover from an interrupted write may have other permissions; never reuse it.
+    let _ = std::fs::remove_file(&temp_path);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|()| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(map_err(err));
+    }
+    Ok(())
+}
+
+/// Writes a cache file like `write_private_json`. Caches are an optimization, so a failed
+/// write is ignored rather than failing the request.
+pub(crate) fn write_cache<T: Serialize>(path: &Path, value: &T) {
+    let _ = write_private_json(path, value);
+}
+
+pub fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_private_json() {
+        let dir = std::env::temp_dir().join(format!("call-mcp-store-{}", std::process::id()));
+        let path = dir.join("tokens.json");
+        write_private_json(&path, &BTreeMap::from([("a", 1)])).unwrap();
+        write_private_json(&path, &BTreeMap::from([("b", 2)])).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\n  \"b\": 2\n}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+use crate::errors::AppError;
+use serde::Deserialize;
+
+use super::store::{StoredToken, now_unix};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+impl TokenResponse {
+    /// Converts a token response into a store entry, keeping the previous refresh token
+    /// when the server does not rotate it.
+    pub(crate) fn into_stored(
+        self,
+        token_endpoint: &str,
+        client_id: &str,
+        client_secret: Option<String>,
+        previous_refresh_token: Option<String>,
+    ) -> StoredToken {
+        StoredToken {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at: self.expires_in.map(|seconds| now_unix() + seconds),
+            scope: self.scope,
+            token_endpoint: token_endpoint.to_string(),
+            client_id: client_id.to_string(),
+            client_secret,
+        }
+    }
+}
+
+/// Posts a form-encoded grant to `token_endpoint` and decodes the token response.
+pub(crate) async fn request_token(
+    client: &reqwest::Client,
+    token_endpoint: &str,
+    form: &[(&str, &str)],
+) -> Result<TokenResponse, AppError> {
+    let response = client
+        .post(token_endpoint)
+        .form(form)
+        .send()
+        .await
+        .map_err(|err| AppError::new("auth_token", format!("Token request failed: {err}")))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|err| AppError::new("auth_token", format!("Token request failed: {err}")))?;
+
+    if !status.is_success() {
+        let message = match serde_json::from_str::<OAuthErrorResponse>(&body) {
+            Ok(OAuthErrorResponse {
+                error,
+                error_description: Some(description),
+            }) => format!("{error}: {description}"),
+            Ok(OAuthErrorResponse { error, .. }) => error,
+            Err(_) => format!("HTTP {status}"),
+        };
+        return Err(AppError::new(
+            "auth_token",
+            format!("Token endpoint rejected request: {message}"),
+        ));
+    }
+
+    serde_json::from_str(&body).map_err(|err| {
+        AppError::new(
+            "auth_token",
+            format!("Invalid token endpoint response: {err}"),
+        )
+    })
+}
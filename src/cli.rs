@@ -1,27 +1,68 @@
+use crate::config::{ImportSource, LogLevel};
+use crate::errors::AppError;
+use crate::mcp_client::{
+    ArgPair, MatchMode, NameFilter, NotificationFormat, NotificationTarget, Pagination,
+};
+use crate::output::OutputFormat;
 use crate::types::Header;
 use crate::user_agent::UserAgentPreset;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
-#[command(name = "call-mcp", version, about = "MCP client bridge CLI")]
+#[command(
+    name = "call-mcp",
+    version,
+    about = "MCP client bridge CLI",
+    arg_required_else_help = true
+)]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
+
+    /// List the exit code of each error category and exit
+    #[arg(long)]
+    pub exit_code_map: bool,
 
     #[arg(long)]
     pub config: Option<PathBuf>,
+
+    /// Output format for results and errors
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Where progress and log notifications go: none, stderr, stdout or file:<path>
+    #[arg(long, global = true, default_value = "stderr")]
+    pub notifications: NotificationTarget,
+
+    /// Encoding of progress and log notifications
+    #[arg(long, global = true, value_enum, default_value_t = NotificationFormat::Text)]
+    pub notification_format: NotificationFormat,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     ListTools(ListArgs),
+    /// Rank tools by name, description and parameter names
+    SearchTools(SearchToolsArgs),
     CallTool(CallToolArgs),
     ListResources(ListArgs),
+    /// List parameterized resources (URI templates)
+    ListResourceTemplates(ListArgs),
     ReadResource(ReadResourceArgs),
+    /// Subscribe to a resource and print its contents on every update
+    WatchResource(WatchResourceArgs),
     ListPrompts(ListArgs),
     GetPrompt(GetPromptArgs),
     GetInfo(GetInfoArgs),
+    /// Ask the server for argument value completions
+    Complete(CompleteArgs),
+    /// Manage OAuth credentials for MCP servers
+    Auth(AuthArgs),
+    /// Inspect call-mcp configuration files
+    Config(ConfigArgs),
+    /// Keep MCP sessions open between commands
+    Daemon(DaemonArgs),
 }
 
 #[derive(Debug, Args)]
@@ -32,9 +73,29 @@ pub struct ListArgs {
     #[arg(long)]
     pub cursor: Option<String>,
 
+    /// Skip this many items of the first page (the next_offset of a truncated listing)
+    #[arg(long)]
+    pub offset: Option<usize>,
+
+    /// Follow next_cursor and merge every page
+    #[arg(long)]
+    pub all: bool,
+
+    /// Query every configured server concurrently; names become <server>:<name>
+    #[arg(long, conflicts_with_all = ["server", "url", "cursor", "offset"])]
+    pub all_servers: bool,
+
+    /// Follow next_cursor until this many items were collected (in total with --all-servers)
+    #[arg(long)]
+    pub limit: Option<usize>,
+
     #[arg(long)]
     pub name: Option<String>,
 
+    /// How --name is matched against item names
+    #[arg(long = "match", value_enum, default_value_t = MatchMode::Exact, requires = "name")]
+    pub match_mode: MatchMode,
+
     #[arg(long)]
     pub require_capability: bool,
 
@@ -43,6 +104,44 @@ pub struct ListArgs {
     pub short: bool,
 }
 
+impl ListArgs {
+    pub fn pagination(&self) -> Pagination {
+        Pagination {
+            cursor: self.cursor.clone(),
+            offset: self.offset,
+            all: self.all,
+            limit: self.limit,
+        }
+    }
+
+    pub fn name_filter(&self) -> Result<Option<NameFilter>, AppError> {
+        self.name
+            .as_deref()
+            .map(|name| NameFilter::new(name, self.match_mode))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SearchToolsArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Words to look for
+    pub query: String,
+
+    /// Number of results to return
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Search every configured server concurrently; names become <server>:<tool>
+    #[arg(long, conflicts_with_all = ["server", "url"])]
+    pub all_servers: bool,
+
+    #[arg(long)]
+    pub require_capability: bool,
+}
+
 #[derive(Debug, Args)]
 pub struct CallToolArgs {
     #[command(flatten)]
@@ -50,9 +149,30 @@ pub struct CallToolArgs {
 
     pub tool: String,
 
+    /// Tool arguments as a JSON object, @<file> or - for stdin
     #[arg(long)]
     pub params: Option<String>,
 
+    /// Argument KEY=VALUE (converted to the type the inputSchema declares) or KEY:=JSON
+    #[arg(long = "arg", value_name = "KEY=VALUE")]
+    pub args: Vec<ArgPair>,
+
+    /// Send --params without checking them against the tool's inputSchema first
+    #[arg(long)]
+    pub no_validate: bool,
+
+    /// Print the tool's structuredContent, validated against its outputSchema
+    #[arg(long)]
+    pub structured: bool,
+
+    /// Save base64 images, audio and blobs into this directory and print their paths
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Ask the server to send log notifications at this level and above
+    #[arg(long, value_enum)]
+    pub log_level: Option<LogLevel>,
+
     #[arg(long)]
     pub require_capability: bool,
 }
@@ -62,8 +182,40 @@ pub struct ReadResourceArgs {
     #[command(flatten)]
     pub connection: ConnectionArgs,
 
+    #[arg(required_unless_present = "template")]
+    pub uri: Option<String>,
+
+    /// RFC 6570 URI template to expand with --params instead of a literal URI
+    #[arg(long, conflicts_with = "uri")]
+    pub template: Option<String>,
+
+    /// Template variables as a JSON object, @<file> or - for stdin
+    #[arg(long, requires = "template")]
+    pub params: Option<String>,
+
+    /// Template variable KEY=VALUE or KEY:=JSON
+    #[arg(long = "arg", value_name = "KEY=VALUE", requires = "template")]
+    pub args: Vec<ArgPair>,
+
+    #[arg(long)]
+    pub require_capability: bool,
+
+    /// Save base64 images, audio and blobs into this directory and print their paths
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchResourceArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
     pub uri: String,
 
+    /// Stop after this many updates (default: until interrupted)
+    #[arg(long)]
+    pub count: Option<u64>,
+
     #[arg(long)]
     pub require_capability: bool,
 }
@@ -75,11 +227,20 @@ pub struct GetPromptArgs {
 
     pub prompt_id: String,
 
+    /// Prompt arguments as a JSON object, @<file> or - for stdin
     #[arg(long)]
     pub params: Option<String>,
 
+    /// Prompt argument KEY=VALUE or KEY:=JSON
+    #[arg(long = "arg", value_name = "KEY=VALUE")]
+    pub args: Vec<ArgPair>,
+
     #[arg(long)]
     pub require_capability: bool,
+
+    /// Save base64 images, audio and blobs into this directory and print their paths
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
@@ -88,6 +249,148 @@ pub struct GetInfoArgs {
     pub connection: ConnectionArgs,
 }
 
+#[derive(Debug, Args)]
+pub struct CompleteArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// What the argument belongs to
+    #[arg(value_enum)]
+    pub kind: CompleteKind,
+
+    /// Prompt name (or <server>:<prompt>), or a resource URI template
+    pub name: String,
+
+    /// Prompt argument or template variable to complete
+    pub argument: String,
+
+    /// Value typed so far
+    #[arg(default_value = "")]
+    pub partial: String,
+
+    #[arg(long)]
+    pub require_capability: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompleteKind {
+    /// Argument of a prompt
+    Prompt,
+    /// Variable of a resource template
+    Resource,
+}
+
+#[derive(Debug, Args)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    pub command: AuthCommand,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Debug, Args)]
+pub struct DaemonArgs {
+    #[command(subcommand)]
+    pub command: DaemonCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DaemonCommand {
+    /// Start the daemon in the background
+    Start(DaemonRunArgs),
+    /// Run the daemon in the foreground
+    Run(DaemonRunArgs),
+    /// Show the daemon and its pooled sessions
+    Status,
+    /// Stop the daemon and close its sessions
+    Stop,
+}
+
+#[derive(Debug, Args)]
+pub struct DaemonRunArgs {
+    /// Close sessions unused for this many seconds, and exit once none are left
+    #[arg(long, default_value_t = 600)]
+    pub idle_timeout: u64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Show the config files that were found
+    Show(ConfigShowArgs),
+    /// Import MCP servers configured in another tool
+    Import(ConfigImportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigShowArgs {
+    /// Print the merged servers with the file each one comes from
+    #[arg(long)]
+    pub resolved: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigImportArgs {
+    /// Tool to import servers from
+    #[arg(long, value_enum)]
+    pub from: ImportSource,
+
+    /// Source config file (default: the tool's standard location)
+    #[arg(long)]
+    pub source: Option<PathBuf>,
+
+    /// Replace servers that already exist in the target config
+    #[arg(long)]
+    pub overwrite: bool,
+
+    /// Show what would be imported without writing the config
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    /// Log in with the OAuth authorization-code flow and store the tokens
+    Login(AuthLoginArgs),
+    /// Remove stored tokens for a server
+    Logout(AuthLogoutArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct AuthLoginArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Pre-registered OAuth client ID (default: dynamic client registration)
+    #[arg(long)]
+    pub client_id: Option<String>,
+
+    /// Environment variable holding the OAuth client secret
+    #[arg(long)]
+    pub client_secret_env: Option<String>,
+
+    /// Scope to request (repeatable; default: scopes advertised by the server)
+    #[arg(long = "scope")]
+    pub scopes: Vec<String>,
+
+    /// Local port for the redirect listener (default: any free port)
+    #[arg(long, default_value_t = 0)]
+    pub redirect_port: u16,
+
+    /// Print the authorization URL without opening a browser
+    #[arg(long)]
+    pub no_browser: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AuthLogoutArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct ConnectionArgs {
     #[arg(long)]
@@ -102,6 +405,10 @@ pub struct ConnectionArgs {
     #[arg(long)]
     pub token_env: Option<String>,
 
+    /// Command whose stdout is used as the bearer token (e.g. "gh auth token")
+    #[arg(long)]
+    pub token_command: Option<String>,
+
     #[arg(long)]
     pub timeout: Option<u64>,
 
@@ -119,6 +426,10 @@ pub struct ConnectionArgs {
     /// Or any custom User-Agent string
     #[arg(long, value_parser = parse_user_agent)]
     pub user_agent: Option<UserAgentPreset>,
+
+    /// Connect directly even when `call-mcp daemon` is running
+    #[arg(long)]
+    pub no_daemon: bool,
 }
 
 fn parse_user_agent(raw: &str) -> Result<UserAgentPreset, String> {
@@ -0,0 +1,243 @@
+use crate::errors::AppError;
+use crate::paths::{home_dir, user_config_dir};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::format::ConfigFormat;
+use super::{ConfigFile, ServerEntry};
+
+const CONFIG_ENV: &str = "CALL_MCP_CONFIG";
+/// Checked in order in each directory; the first existing file is used.
+const PROJECT_FILES: [&str; 8] = [
+    ".mcp.json",
+    "mcp.json",
+    ".mcp.yaml",
+    "mcp.yaml",
+    ".mcp.yml",
+    "mcp.yml",
+    ".mcp.toml",
+    "mcp.toml",
+];
+const USER_FILES: [&str; 4] = ["config.json", "config.yaml", "config.yml", "config.toml"];
+
+/// Server entry together with the config file that defined it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourcedEntry {
+    pub source: PathBuf,
+    #[serde(flatten)]
+    pub entry: ServerEntry,
+}
+
+/// Config files merged by precedence; a server defined in a later file replaces the
+/// same server from an earlier one.
+#[derive(Debug, Default)]
+pub struct LoadedConfig {
+    /// Files that were read, from lowest to highest precedence.
+    pub files: Vec<PathBuf>,
+    pub servers: BTreeMap<String, SourcedEntry>,
+}
+
+impl LoadedConfig {
+    /// Loads `explicit` alone when given, otherwise every discovered config file.
+    pub fn load(explicit: Option<PathBuf>) -> Result<Self, AppError> {
+        Ok(Self::merge(read_layers(explicit)?))
+    }
+
+    fn merge(layers: Vec<(PathBuf, ConfigFile)>) -> Self {
+        let mut loaded = Self::default();
+        for (path, config) in layers {
+            for (name, entry) in config.mcp_servers {
+                loaded.servers.insert(
+                    name,
+                    SourcedEntry {
+                        source: path.clone(),
+                        entry,
+                    },
+                );
+            }
+            loaded.files.push(path);
+        }
+        loaded
+    }
+}
+
+/// Lists the discovered config files, or the merged servers when `resolved` is set.
+///
+/// Values are shown as written; `${VAR}` references are not expanded so secrets stay out
+/// of the output.
+pub fn show_config(explicit: Option<PathBuf>, resolved: bool) -> Result<Value, AppError> {
+    if resolved {
+        let loaded = LoadedConfig::load(explicit)?;
+        return Ok(json!({
+            "files": loaded.files,
+            "mcpServers": loaded.servers,
+        }));
+    }
+
+    let files: Vec<Value> = read_layers(explicit)?
+        .into_iter()
+        .map(|(path, config)| {
+            json!({
+                "path": path,
+                "mcpServers": config.mcp_servers,
+            })
+        })
+        .collect();
+    Ok(json!({ "files": files }))
+}
+
+/// Project config file in the current directory that commands write to: the first
+/// existing project file, otherwise a new `.mcp.json`.
+pub fn default_config_path() -> PathBuf {
+    PROJECT_FILES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(PROJECT_FILES[0]))
+}
+
+fn read_layers(explicit: Option<PathBuf>) -> Result<Vec<(PathBuf, ConfigFile)>, AppError> {
+    config_paths(explicit)?
+        .into_iter()
+        .map(|path| {
+            let config = read_config_file(&path)?;
+            Ok((path, config))
+        })
+        .collect()
+}
+
+/// Config files in increasing precedence: the user config, project files from the
+/// outermost project directory down to the current one, then `$CALL_MCP_CONFIG`.
+/// An explicit `--config` path disables discovery.
+fn config_paths(explicit: Option<PathBuf>) -> Result<Vec<PathBuf>, AppError> {
+    if let Some(path) = explicit {
+        return Ok(vec![require_file(path)?]);
+    }
+
+    let mut paths = Vec::new();
+    if let Some(dir) = user_config_dir() {
+        paths.extend(
+            USER_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file()),
+        );
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        let mut project: Vec<PathBuf> = project_dirs(&cwd, home_dir().as_deref())
+            .into_iter()
+            .filter_map(|dir| {
+                PROJECT_FILES
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .find(|path| path.is_file())
+            })
+            .collect();
+        project.reverse();
+        paths.extend(project);
+    }
+    paths.retain(|path| path.is_file());
+
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|value| !value.is_empty()) {
+        let path = require_file(PathBuf::from(path))?;
+        paths.retain(|existing| !same_file(existing, &path));
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Directories searched for project files, from `cwd` upwards. The search stops at the
+/// repository root (the first directory with a `.git`) or at `home`, so files in shared
+/// directories such as `/tmp` are not picked up; outside both only `cwd` is searched.
+fn project_dirs<'a>(cwd: &'a Path, home: Option<&Path>) -> Vec<&'a Path> {
+    let mut dirs = Vec::new();
+    for dir in cwd.ancestors() {
+        dirs.push(dir);
+        if dir.join(".git").exists() || Some(dir) == home {
+            return dirs;
+        }
+    }
+    vec![cwd]
+}
+
+fn require_file(path: PathBuf) -> Result<PathBuf, AppError> {
+    if !path.exists() {
+        return Err(AppError::new(
+            "config_not_found",
+            format!("Config file not found: {}", path.display()),
+        ));
+    }
+    Ok(path)
+}
+
+fn same_file(left: &Path, right: &Path) -> bool {
+    match (left.canonicalize(), right.canonicalize()) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile, AppError> {
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        AppError::new(
+            "config_read",
+            format!("Failed to read config: {} ({err})", path.display()),
+        )
+    })?;
+
+    ConfigFormat::detect(path, &contents).parse(path, &contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(path: &str, json: &str) -> (PathBuf, ConfigFile) {
+        (PathBuf::from(path), serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let loaded = LoadedConfig::merge(vec![
+            layer(
+                "user.json",
+                r#"{"mcpServers":{"a":{"url":"https://user/a"},"b":{"url":"https://user/b"}}}"#,
+            ),
+            layer("project.json", r#"{"mcpServers":{"a":{"command":"node"}}}"#),
+        ]);
+
+        assert_eq!(
+            loaded.files,
+            vec![PathBuf::from("user.json"), PathBuf::from("project.json")]
+        );
+        let a = &loaded.servers["a"];
+        assert_eq!(a.source, PathBuf::from("project.json"));
+        assert_eq!(a.entry.command.as_deref(), Some("node"));
+        assert_eq!(a.entry.url, None);
+        assert_eq!(loaded.servers["b"].source, PathBuf::from("user.json"));
+    }
+
+    #[test]
+    fn test_project_dirs() {
+        let root = std::env::temp_dir().join(format!("call-mcp-dirs-{}", std::process::id()));
+        let cwd = root.join("repo/crates/app");
+        std::fs::create_dir_all(&cwd).unwrap();
+        let repo = root.join("repo");
+
+        // Outside a repository and the home directory only `cwd` is searched.
+        assert_eq!(project_dirs(&cwd, None), vec![cwd.as_path()]);
+        assert_eq!(
+            project_dirs(&cwd, Some(&repo)),
+            vec![cwd.as_path(), &repo.join("crates"), &repo]
+        );
+        std::fs::create_dir(repo.join(".git")).unwrap();
+        assert_eq!(
+            project_dirs(&cwd, None),
+            vec![cwd.as_path(), &repo.join("crates"), &repo]
+        );
+        assert_eq!(project_dirs(&repo, Some(&root)), vec![repo.as_path()]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use crate::errors::AppError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::path::Path;
+
+/// Config file syntax; all formats share the `ConfigFile` schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    /// Picks the format from the file extension, sniffing the contents for unknown ones.
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("json") => ConfigFormat::Json,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => Self::sniff(contents),
+        }
+    }
+
+    fn sniff(contents: &str) -> Self {
+        let mut lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        match lines.next() {
+            Some(line) if line.starts_with('{') => ConfigFormat::Json,
+            Some(line) if line.starts_with('[') || is_toml_assignment(line) => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    /// Parses `contents`, reporting the line and column of syntax and schema errors.
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, contents: &str) -> Result<T, AppError> {
+        let (message, position) = match self {
+            ConfigFormat::Json => match serde_json::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    let suffix = format!(" at line {} column {}", err.line(), err.column());
+                    let message = err.to_string();
+                    let message = message
+                        .strip_suffix(&suffix)
+                        .unwrap_or(&message)
+                        .to_string();
+                    (message, Some((err.line(), err.column())))
+                }
+            },
+            ConfigFormat::Yaml => match serde_saphyr::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    let position = err
+                        .location()
+                        .map(|location| (location.line() as usize, location.column() as usize));
+                    (yaml_message(&err.to_string(), position), position)
+                }
+            },
+            ConfigFormat::Toml => match toml::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    let position = err.span().map(|span| line_column(contents, span.start));
+                    (err.message().to_string(), position)
+                }
+            },
+        };
+
+        let location = match position {
+            Some((line, column)) => format!("{}:{line}:{column}", path.display()),
+            None => path.display().to_string(),
+        };
+        Err(AppError::new(
+            "config_parse",
+            format!("Failed to parse config: {location}: {message}"),
+        )
+        .with_details(json!({
+            "path": path,
+            "format": self.as_str(),
+            "line": position.map(|(line, _)| line),
+            "column": position.map(|(_, column)| column),
+        })))
+    }
+
+    /// Renders a config document in this format.
+    pub fn render<T: Serialize>(self, value: &T) -> Result<String, AppError> {
+        let encode_err =
+            |err: String| AppError::new("config_encode", format!("Failed to encode config: {err}"));
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value)
+                .map(|contents| contents + "\n")
+                .map_err(|err| encode_err(err.to_string())),
+            ConfigFormat::Yaml => {
+                serde_saphyr::to_string(value).map_err(|err| encode_err(err.to_string()))
+            }
+            ConfigFormat::Toml => {
+                toml::to_string_pretty(value).map_err(|err| encode_err(err.to_string()))
+            }
+        }
+    }
+}
+
+fn is_toml_assignment(line: &str) -> bool {
+    line.split_once('=').is_some_and(|(key, _)| {
+        let key = key.trim();
+        !key.is_empty()
+            && !key.contains(':')
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-.\"' ".contains(c))
+    })
+}
+
+/// 1-based line and column of a byte offset.
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Keeps the first line of a serde-saphyr error without its repeated location text.
+fn yaml_message(raw: &str, position: Option<(usize, usize)>) -> String {
+    let mut message = raw.lines().next().unwrap_or_default();
+    message = message.strip_prefix("error: ").unwrap_or(message);
+    if let Some((line, column)) = position {
+        let prefix = format!("line {line} column {column}: ");
+        let suffix = format!(" at line {line}, column {column}");
+        message = message.strip_prefix(&prefix).unwrap_or(message);
+        message = message.strip_suffix(&suffix).unwrap_or(message);
+    }
+    message.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFile;
+
+    #[test]
+    fn test_detect() {
+        let path = Path::new("config");
+        assert_eq!(
+            ConfigFormat::detect(Path::new("mcp.yml"), ""),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::detect(path, "{\"mcpServers\": {}}"),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::detect(path, "# servers\n[mcpServers.docs]\nurl = \"x\""),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::detect(path, "mcpServers:\n  docs:\n    url: x"),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let yaml = "mcpServers:\n  docs:\n    url: https://example.com/mcp\n    headers:\n      Authorization: >-\n        Bearer\n        token\n";
+        let config: ConfigFile = ConfigFormat::Yaml
+            .parse(Path::new("mcp.yaml"), yaml)
+            .unwrap();
+        assert_eq!(
+            config.mcp_servers["docs"].headers["Authorization"],
+            "Bearer token"
+        );
+
+        let toml = "[mcpServers.docs]\ncommand = \"node\"\nargs = [\"server.js\"]\n";
+        let config: ConfigFile = ConfigFormat::Toml
+            .parse(Path::new("mcp.toml"), toml)
+            .unwrap();
+        assert_eq!(config.mcp_servers["docs"].args, vec!["server.js"]);
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let cases = [
+            (
+                ConfigFormat::Json,
+                "{\n  \"mcpServers\": {\n    \"a\": {\"retry\": \"x\"}\n  }\n}",
+                3,
+            ),
+            (ConfigFormat::Yaml, "mcpServers:\n  a:\n    retry: x\n", 3),
+            (
+                ConfigFormat::Toml,
+                "[mcpServers.a]\nurl = \"u\"\nretry = \"x\"\n",
+                3,
+            ),
+        ];
+        for (format, contents, line) in cases {
+            let err = format
+                .parse::<ConfigFile>(Path::new("mcp"), contents)
+                .unwrap_err();
+            assert_eq!(err.code(), "config_parse");
+            let details = err.details().unwrap();
+            assert_eq!(details["line"], line, "{format:?}: {}", err.message());
+            assert!(details["column"].as_u64().unwrap() > 0);
+        }
+    }
+}
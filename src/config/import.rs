@@ -0,0 +1,679 @@
+use crate::errors::AppError;
+use crate::paths::home_dir;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::format::ConfigFormat;
+use super::{ServerEntry, TransportKind};
+
+/// Editors and agents whose MCP server configs can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportSource {
+    ClaudeDesktop,
+    Cursor,
+    Vscode,
+    Codex,
+}
+
+impl ImportSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            ImportSource::ClaudeDesktop => "claude-desktop",
+            ImportSource::Cursor => "cursor",
+            ImportSource::Vscode => "vscode",
+            ImportSource::Codex => "codex",
+        }
+    }
+
+    /// Config file locations checked in order when no source path is given.
+    fn default_paths(self) -> Vec<PathBuf> {
+        let home = home_dir();
+        match self {
+            ImportSource::ClaudeDesktop => {
+                let base = if cfg!(windows) {
+                    std::env::var_os("APPDATA").map(PathBuf::from)
+                } else if cfg!(target_os = "macos") {
+                    home.map(|home| home.join("Library").join("Application Support"))
+                } else {
+                    std::env::var_os("XDG_CONFIG_HOME")
+                        .filter(|value| !value.is_empty())
+                        .map(PathBuf::from)
+                        .or_else(|| home.map(|home| home.join(".config")))
+                };
+                base.map(|base| base.join("Claude").join("claude_desktop_config.json"))
+                    .into_iter()
+                    .collect()
+            }
+            ImportSource::Cursor => {
+                let mut paths = vec![PathBuf::from(".cursor").join("mcp.json")];
+                paths.extend(home.map(|home| home.join(".cursor").join("mcp.json")));
+                paths
+            }
+            ImportSource::Vscode => vec![PathBuf::from(".vscode").join("mcp.json")],
+            ImportSource::Codex => std::env::var_os("CODEX_HOME")
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home.map(|home| home.join(".codex")))
+                .map(|dir| dir.join("config.toml"))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Options for `config import`.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub from: ImportSource,
+    /// Source config file; the tool's default location when absent.
+    pub source: Option<PathBuf>,
+    /// Config file to write into.
+    pub target: PathBuf,
+    pub overwrite: bool,
+    pub dry_run: bool,
+}
+
+/// Server converted from another tool, or the reason it cannot be used.
+type Converted = Result<(ServerEntry, Vec<String>), String>;
+
+/// Copies MCP servers from another tool's config into `options.target`.
+///
+/// Entries whose transport call-mcp cannot handle are reported under `skipped`, and
+/// values that may need manual attention (editor input prompts, env files) under `warnings`.
+pub fn import_servers(options: ImportOptions) -> Result<Value, AppError> {
+    let source = match options.source {
+        Some(path) if path.is_file() => path,
+        Some(path) => {
+            return Err(AppError::new(
+                "config_not_found",
+                format!("Config file not found: {}", path.display()),
+            ));
+        }
+        None => {
+            let candidates = options.from.default_paths();
+            candidates
+                .iter()
+                .find(|path| path.is_file())
+                .cloned()
+                .ok_or_else(|| {
+                    let tried: Vec<String> = candidates
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect();
+                    AppError::new(
+                        "config_not_found",
+                        format!(
+                            "No {} config found (tried: {}); pass --source",
+                            options.from.as_str(),
+                            tried.join(", ")
+                        ),
+                    )
+                })?
+        }
+    };
+
+    let contents = std::fs::read_to_string(&source).map_err(|err| {
+        AppError::new(
+            "config_read",
+            format!("Failed to read config: {} ({err})", source.display()),
+        )
+    })?;
+    let servers = match options.from {
+        ImportSource::Codex => parse_codex(&contents, &source)?,
+        ImportSource::Vscode => parse_json(&contents, &source, "servers", workspace_dir(&source))?,
+        ImportSource::ClaudeDesktop | ImportSource::Cursor => {
+            parse_json(&contents, &source, "mcpServers", workspace_dir(&source))?
+        }
+    };
+
+    let Target {
+        config: mut target,
+        format,
+        original,
+    } = read_target(&options.target)?;
+    let existing = target
+        .entry("mcpServers")
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Some(existing) = existing.as_object_mut() else {
+        return Err(AppError::new(
+            "config_parse",
+            format!(
+                "Failed to parse config: {} (mcpServers is not an object)",
+                options.target.display()
+            ),
+        ));
+    };
+
+    let mut imported = Vec::new();
+    let mut added = Map::new();
+    let mut replaced = false;
+    let mut skipped = Vec::new();
+    let mut warnings = Vec::new();
+    for (name, converted) in servers {
+        let (entry, notes) = match converted {
+            Ok(converted) => converted,
+            Err(reason) => {
+                skipped.push(json!({ "name": name, "reason": reason }));
+                continue;
+            }
+        };
+        if existing.contains_key(&name) && !options.overwrite {
+            skipped.push(json!({
+                "name": name,
+                "reason": "already defined in target (use --overwrite to replace)",
+            }));
+            continue;
+        }
+
+        warnings.extend(notes.into_iter().map(|note| format!("{name}: {note}")));
+        let value = serde_json::to_value(&entry).map_err(|err| {
+            AppError::new("json_encode", format!("Failed to encode config: {err}"))
+        })?;
+        replaced |= existing.contains_key(&name);
+        existing.insert(name.clone(), value.clone());
+        added.insert(name.clone(), value);
+        imported.push(name);
+    }
+
+    if !imported.is_empty() {
+        let contents = match &original {
+            Some(original) if !replaced => append_servers(original, format, &added, &target),
+            _ => None,
+        };
+        let contents = match (contents, &original) {
+            (Some(contents), _) => contents,
+            (None, Some(original)) if has_comments(original, format) => {
+                return Err(AppError::new(
+                    "config_write",
+                    format!(
+                        "Cannot import into {} without losing its comments{}; remove them or import into another file",
+                        options.target.display(),
+                        if replaced {
+                            " when replacing servers"
+                        } else {
+                            ""
+                        }
+                    ),
+                ));
+            }
+            (None, _) => format.render(&target)?,
+        };
+        if !options.dry_run {
+            write_target(&options.target, &contents)?;
+        }
+    }
+
+    Ok(json!({
+        "from": options.from.as_str(),
+        "source": source,
+        "target": options.target,
+        "dry_run": options.dry_run,
+        "imported": imported,
+        "skipped": skipped,
+        "warnings": warnings,
+    }))
+}
+
+/// Directory that `${workspaceFolder}` refers to: the parent of `.vscode`/`.cursor`.
+fn workspace_dir(source: &Path) -> Option<PathBuf> {
+    let dir = source.parent()?;
+    let name = dir.file_name()?.to_str()?;
+    if name != ".vscode" && name != ".cursor" {
+        return None;
+    }
+    let workspace = dir.parent()?;
+    let workspace = if workspace.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        workspace
+    };
+    workspace.canonicalize().ok()
+}
+
+fn parse_json(
+    contents: &str,
+    source: &Path,
+    key: &str,
+    workspace: Option<PathBuf>,
+) -> Result<Vec<(String, Converted)>, AppError> {
+    let root: Value = serde_json::from_str(contents).map_err(|err| {
+        AppError::new(
+            "config_parse",
+            format!("Failed to parse config: {} ({err})", source.display()),
+        )
+    })?;
+    let Some(servers) = root.get(key).and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(servers
+        .iter()
+        .map(|(name, value)| (name.clone(), convert_json(value, workspace.as_deref())))
+        .collect())
+}
+
+/// Converts a Claude Desktop / Cursor / VS Code server object.
+fn convert_json(value: &Value, workspace: Option<&Path>) -> Converted {
+    let object = value
+        .as_object()
+        .ok_or_else(|| "server entry is not an object".to_string())?;
+    let mut notes = Vec::new();
+    let mut text = |value: &str| convert_placeholders(value, workspace, &mut notes);
+
+    let string = |field: &str| object.get(field).and_then(Value::as_str);
+    let map = |field: &str| -> BTreeMap<String, String> {
+        object
+            .get(field)
+            .and_then(Value::as_object)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let command = string("command").map(&mut text);
+    let url = string("url").or_else(|| string("serverUrl")).map(&mut text);
+    let kind = match string("type") {
+        Some(raw) => TransportKind::parse(raw).map_err(|err| err.message().to_string())?,
+        None if command.is_some() => TransportKind::Stdio,
+        None if url.is_some() => TransportKind::Http,
+        None => return Err("entry has neither command nor url".to_string()),
+    };
+
+    let mut entry = ServerEntry::default();
+    match kind {
+        TransportKind::Stdio => {
+            let Some(command) = command else {
+                return Err("stdio entry has no command".to_string());
+            };
+            entry.server_type = Some("stdio".to_string());
+            entry.command = Some(command);
+            entry.args = object
+                .get("args")
+                .and_then(Value::as_array)
+                .map(|args| {
+                    args.iter()
+                        .filter_map(Value::as_str)
+                        .map(&mut text)
+                        .collect()
+                })
+                .unwrap_or_default();
+            entry.env = map("env")
+                .into_iter()
+                .map(|(key, value)| (key, text(&value)))
+                .collect();
+            entry.cwd = string("cwd").map(|cwd| PathBuf::from(text(cwd)));
+        }
+        TransportKind::Http | TransportKind::Sse => {
+            let Some(url) = url else {
+                return Err("remote entry has no url".to_string());
+            };
+            entry.server_type = Some(
+                if kind == TransportKind::Sse {
+                    "sse"
+                } else {
+                    "http"
+                }
+                .to_string(),
+            );
+            entry.url = Some(url);
+            entry.headers = map("headers")
+                .into_iter()
+                .map(|(key, value)| (key, text(&value)))
+                .collect();
+        }
+    }
+
+    if object.contains_key("envFile") {
+        notes.push("envFile is not supported; copy the variables into env".to_string());
+    }
+    Ok((entry, notes))
+}
+
+/// Rewrites editor placeholders: `${env:VAR}` becomes `${VAR}` and `${workspaceFolder}`
+/// the workspace path. Other placeholders (e.g. `${input:...}`) are kept and flagged.
+fn convert_placeholders(value: &str, workspace: Option<&Path>, notes: &mut Vec<String>) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+        let reference = &after[..end];
+        let raw = &rest[start..start + end + 3];
+        if let Some(name) = reference.strip_prefix("env:") {
+            output.push_str(&format!("${{{name}}}"));
+        } else if reference == "workspaceFolder"
+            && let Some(workspace) = workspace
+        {
+            output.push_str(&workspace.display().to_string());
+        } else {
+            if reference.contains(':') || reference == "workspaceFolder" {
+                notes.push(format!(
+                    "placeholder {raw} cannot be resolved; edit the value manually"
+                ));
+            }
+            output.push_str(raw);
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CodexConfig {
+    #[serde(default)]
+    mcp_servers: BTreeMap<String, CodexServer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodexServer {
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    bearer_token_env_var: Option<String>,
+    #[serde(default)]
+    http_headers: BTreeMap<String, String>,
+    /// Header name to the environment variable holding its value.
+    #[serde(default)]
+    env_http_headers: BTreeMap<String, String>,
+    #[serde(default)]
+    startup_timeout_sec: Option<f64>,
+    #[serde(default)]
+    tool_timeout_sec: Option<f64>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn parse_codex(contents: &str, source: &Path) -> Result<Vec<(String, Converted)>, AppError> {
+    let config: CodexConfig = toml::from_str(contents).map_err(|err| {
+        AppError::new(
+            "config_parse",
+            format!("Failed to parse config: {} ({err})", source.display()),
+        )
+    })?;
+
+    Ok(config
+        .mcp_servers
+        .into_iter()
+        .map(|(name, server)| (name, convert_codex(server)))
+        .collect())
+}
+
+fn convert_codex(server: CodexServer) -> Converted {
+    if !server.enabled {
+        return Err("disabled in source config".to_string());
+    }
+
+    let mut entry = ServerEntry {
+        timeout: server.tool_timeout_sec.map(seconds_to_ms),
+        connect_timeout: server.startup_timeout_sec.map(seconds_to_ms),
+        ..ServerEntry::default()
+    };
+    match (server.command, server.url) {
+        (Some(command), None) => {
+            entry.server_type = Some("stdio".to_string());
+            entry.command = Some(command);
+            entry.args = server.args;
+            entry.env = server.env;
+            entry.cwd = server.cwd;
+        }
+        (None, Some(url)) => {
+            entry.server_type = Some("http".to_string());
+            entry.url = Some(url);
+            entry.token_env = server.bearer_token_env_var;
+            entry.headers = server.http_headers;
+            entry.headers.extend(
+                server
+                    .env_http_headers
+                    .into_iter()
+                    .map(|(header, var)| (header, format!("${{{var}}}"))),
+            );
+        }
+        (Some(_), Some(_)) => return Err("entry has both command and url".to_string()),
+        (None, None) => return Err("entry has neither command nor url".to_string()),
+    }
+    Ok((entry, Vec::new()))
+}
+
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds * 1000.0).round() as u64
+}
+
+/// Config file servers are imported into.
+struct Target {
+    config: Map<String, Value>,
+    format: ConfigFormat,
+    /// Text of the file, when it exists.
+    original: Option<String>,
+}
+
+fn read_target(path: &Path) -> Result<Target, AppError> {
+    if !path.exists() {
+        return Ok(Target {
+            config: Map::new(),
+            format: ConfigFormat::detect(path, ""),
+            original: None,
+        });
+    }
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        AppError::new(
+            "config_read",
+            format!("Failed to read config: {} ({err})", path.display()),
+        )
+    })?;
+    let format = ConfigFormat::detect(path, &contents);
+    Ok(Target {
+        config: format.parse(path, &contents)?,
+        format,
+        original: Some(contents),
+    })
+}
+
+/// Adds `servers` to the end of a YAML or TOML config without touching the rest of the
+/// text, so comments and formatting survive. Returns `None` when the text cannot be
+/// extended that way, or the result would not parse to `expected`.
+fn append_servers(
+    original: &str,
+    format: ConfigFormat,
+    servers: &Map<String, Value>,
+    expected: &Map<String, Value>,
+) -> Option<String> {
+    let mut contents = original.to_string();
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    match format {
+        ConfigFormat::Json => return None,
+        ConfigFormat::Toml => {
+            // New `[mcpServers.<name>]` tables may follow any other table.
+            let tables = format.render(&json!({ "mcpServers": servers })).ok()?;
+            contents.push('\n');
+            contents.push_str(&tables);
+        }
+        ConfigFormat::Yaml => {
+            let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+            let header = lines.iter().position(|line| {
+                line.strip_prefix("mcpServers:").is_some_and(|rest| {
+                    matches!(rest.trim_start().chars().next(), None | Some('#'))
+                })
+            });
+            let Some(header) = header else {
+                contents.push_str(&format.render(&json!({ "mcpServers": servers })).ok()?);
+                return parses_to(&contents, format, expected);
+            };
+            // The block ends before the next line that starts at column 0.
+            let children: Vec<usize> = (header + 1..lines.len())
+                .take_while(|&index| {
+                    let line = lines[index];
+                    line.trim().is_empty() || line.starts_with([' ', '\t']) || line.starts_with('#')
+                })
+                .filter(|&index| {
+                    let trimmed = lines[index].trim_start();
+                    !trimmed.is_empty() && !trimmed.starts_with('#')
+                })
+                .collect();
+            let (&first, &last) = (children.first()?, children.last()?);
+            let indent = &lines[first][..lines[first].len() - lines[first].trim_start().len()];
+            let entries: String = format
+                .render(servers)
+                .ok()?
+                .split_inclusive('\n')
+                .map(|line| {
+                    if line.trim().is_empty() {
+                        line.to_string()
+                    } else {
+                        format!("{indent}{line}")
+                    }
+                })
+                .collect();
+            contents = lines[..=last].concat() + &entries + &lines[last + 1..].concat();
+        }
+    }
+    parses_to(&contents, format, expected)
+}
+
+fn parses_to(
+    contents: &str,
+    format: ConfigFormat,
+    expected: &Map<String, Value>,
+) -> Option<String> {
+    let parsed: Map<String, Value> = format.parse(Path::new(""), contents).ok()?;
+    (parsed == *expected).then(|| contents.to_string())
+}
+
+/// Whether a YAML or TOML config has comments a full rewrite would drop.
+fn has_comments(contents: &str, format: ConfigFormat) -> bool {
+    format != ConfigFormat::Json
+        && contents.lines().any(|line| {
+            line.trim_start().starts_with('#') || line.contains(" #") || line.contains("\t#")
+        })
+}
+
+fn write_target(path: &Path, contents: &str) -> Result<(), AppError> {
+    std::fs::write(path, contents).map_err(|err| {
+        AppError::new(
+            "config_write",
+            format!("Failed to write config: {} ({err})", path.display()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_vscode_entry() {
+        let value = json!({
+            "type": "http",
+            "url": "https://api.example.com/mcp",
+            "headers": { "Authorization": "Bearer ${env:API_TOKEN}", "X-Key": "${input:key}" }
+        });
+        let (entry, notes) = convert_json(&value, None).unwrap();
+        assert_eq!(entry.server_type.as_deref(), Some("http"));
+        assert_eq!(
+            entry.headers.get("Authorization").map(String::as_str),
+            Some("Bearer ${API_TOKEN}")
+        );
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("input:key"));
+
+        let unsupported = json!({ "type": "websocket", "url": "wss://example.com" });
+        assert!(convert_json(&unsupported, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_codex() {
+        let contents = r#"
+[mcp_servers.docs]
+command = "npx"
+args = ["-y", "docs-mcp"]
+env = { DEBUG = "1" }
+tool_timeout_sec = 30
+
+[mcp_servers.remote]
+url = "https://mcp.example.com/mcp"
+bearer_token_env_var = "REMOTE_TOKEN"
+env_http_headers = { "X-Api-Key" = "REMOTE_KEY" }
+
+[mcp_servers.off]
+command = "off"
+enabled = false
+"#;
+        let servers: BTreeMap<String, Converted> = parse_codex(contents, Path::new("config.toml"))
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        let (docs, _) = servers["docs"].clone().unwrap();
+        assert_eq!(docs.command.as_deref(), Some("npx"));
+        assert_eq!(docs.args, vec!["-y", "docs-mcp"]);
+        assert_eq!(docs.timeout, Some(30_000));
+
+        let (remote, _) = servers["remote"].clone().unwrap();
+        assert_eq!(remote.token_env.as_deref(), Some("REMOTE_TOKEN"));
+        assert_eq!(
+            remote.headers.get("X-Api-Key").map(String::as_str),
+            Some("${REMOTE_KEY}")
+        );
+
+        assert!(servers["off"].is_err());
+    }
+
+    #[test]
+    fn test_append_servers() {
+        let servers = json!({ "new": { "type": "stdio", "command": "node", "args": ["a.js"] } });
+        let servers = servers.as_object().unwrap();
+        let expect = |format: ConfigFormat, original: &str| {
+            let mut expected: Map<String, Value> = format.parse(Path::new(""), original).unwrap();
+            let existing = expected
+                .entry("mcpServers")
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .unwrap();
+            existing.extend(servers.clone());
+            append_servers(original, format, servers, &expected)
+        };
+
+        let yaml = "# project servers\nmcpServers:\n    docs:  # remote\n        url: https://example.com/mcp\n\n# tuning\nretry: 2\n";
+        let contents = expect(ConfigFormat::Yaml, yaml).unwrap();
+        assert!(contents.starts_with("# project servers\nmcpServers:\n    docs:  # remote\n"));
+        assert!(contents.contains("\n    new:\n"));
+        assert!(contents.ends_with("\n# tuning\nretry: 2\n"));
+
+        let yaml = "retry: 2 # attempts\n";
+        let contents = expect(ConfigFormat::Yaml, yaml).unwrap();
+        assert!(contents.starts_with("retry: 2 # attempts\nmcpServers:\n"));
+
+        let toml = "# shared\n[mcpServers.docs]\nurl = \"https://example.com/mcp\" # remote\n";
+        let contents = expect(ConfigFormat::Toml, toml).unwrap();
+        assert!(contents.starts_with(toml));
+        assert!(contents.contains("[mcpServers.new]"));
+
+        let flow = "mcpServers: { docs: { url: x } }  # inline\n";
+        assert!(expect(ConfigFormat::Yaml, flow).is_none());
+        assert!(has_comments(flow, ConfigFormat::Yaml));
+    }
+}
@@ -0,0 +1,567 @@
+mod discovery;
+mod format;
+mod import;
+
+use crate::cli::ConnectionArgs;
+use crate::errors::AppError;
+use crate::types::Header;
+use crate::user_agent::UserAgentPreset;
+use rmcp::model::LoggingLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub use discovery::{LoadedConfig, default_config_path, show_config};
+pub use import::{ImportOptions, ImportSource, import_servers};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ConfigFile {
+    #[serde(rename = "mcpServers", default)]
+    pub mcp_servers: BTreeMap<String, ServerEntry>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct ServerEntry {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub server_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<CommandSpec>,
+    /// Seconds to cache the `token_command` output; not cached when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command_ttl: Option<u64>,
+    #[serde(default, alias = "timeout_ms", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(
+        default,
+        alias = "connect_timeout_ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub connect_timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<u32>,
+    #[serde(
+        default,
+        alias = "retry_backoff_ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub retry_backoff: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Minimum level of log notifications requested with `logging/setLevel`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth: Option<OAuthConfig>,
+}
+
+impl ServerEntry {
+    /// Expands `${VAR}` and `${VAR:-default}` references in the entry's string fields.
+    fn expand_env(&mut self, server: &str) -> Result<(), AppError> {
+        let expand = |value: &mut String| -> Result<(), AppError> {
+            *value = expand_env_str(value, |name| std::env::var(name).ok()).map_err(|err| {
+                AppError::new(
+                    err.code().to_owned(),
+                    format!("{} (server {server})", err.message()),
+                )
+            })?;
+            Ok(())
+        };
+
+        for value in [
+            &mut self.server_type,
+            &mut self.url,
+            &mut self.command,
+            &mut self.token_env,
+            &mut self.user_agent,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            expand(value)?;
+        }
+        for value in self
+            .args
+            .iter_mut()
+            .chain(self.env.values_mut())
+            .chain(self.headers.values_mut())
+        {
+            expand(value)?;
+        }
+        if let Some(cwd) = &mut self.cwd
+            && let Some(raw) = cwd.to_str()
+        {
+            let mut value = raw.to_string();
+            expand(&mut value)?;
+            *cwd = PathBuf::from(value);
+        }
+        match &mut self.token_command {
+            Some(CommandSpec::Shell(line)) => expand(line)?,
+            Some(CommandSpec::Argv(argv)) => argv.iter_mut().try_for_each(expand)?,
+            None => {}
+        }
+        if let Some(oauth) = &mut self.oauth {
+            expand(&mut oauth.token_url)?;
+            expand(&mut oauth.client_id)?;
+            oauth.scopes.iter_mut().try_for_each(expand)?;
+        }
+        Ok(())
+    }
+}
+
+/// External program given either as a shell command line or as an argv array.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CommandSpec {
+    Shell(String),
+    Argv(Vec<String>),
+}
+
+impl std::fmt::Display for CommandSpec {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandSpec::Shell(line) => write!(formatter, "{line}"),
+            CommandSpec::Argv(argv) => write!(formatter, "{}", argv.join(" ")),
+        }
+    }
+}
+
+/// MCP logging level, from most to least verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl From<LogLevel> for LoggingLevel {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => LoggingLevel::Debug,
+            LogLevel::Info => LoggingLevel::Info,
+            LogLevel::Notice => LoggingLevel::Notice,
+            LogLevel::Warning => LoggingLevel::Warning,
+            LogLevel::Error => LoggingLevel::Error,
+            LogLevel::Critical => LoggingLevel::Critical,
+            LogLevel::Alert => LoggingLevel::Alert,
+            LogLevel::Emergency => LoggingLevel::Emergency,
+        }
+    }
+}
+
+/// Credential helper whose stdout is used as the bearer token.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TokenCommand {
+    pub command: CommandSpec,
+    pub ttl: Option<u64>,
+}
+
+/// OAuth client-credentials grant settings for headless servers.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OAuthConfig {
+    pub token_url: String,
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+/// How the client reaches the MCP server.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum TransportTarget {
+    /// Streamable HTTP endpoint, falling back to HTTP+SSE on a 4xx initialize response.
+    Http { url: String },
+    /// Legacy (2024-11-05) HTTP+SSE endpoint.
+    Sse { url: String },
+    /// Local server spawned as a child process speaking MCP over stdio.
+    Stdio(StdioCommand),
+}
+
+impl TransportTarget {
+    /// Server URL for HTTP-based transports.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            TransportTarget::Http { url } | TransportTarget::Sse { url } => Some(url),
+            TransportTarget::Stdio(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StdioCommand {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransportKind {
+    Http,
+    Sse,
+    Stdio,
+}
+
+impl TransportKind {
+    fn parse(raw: &str) -> Result<Self, AppError> {
+        match raw.to_ascii_lowercase().as_str() {
+            "http" | "streamable-http" | "streamable_http" | "streamablehttp" => {
+                Ok(TransportKind::Http)
+            }
+            "sse" => Ok(TransportKind::Sse),
+            "stdio" => Ok(TransportKind::Stdio),
+            _ => Err(AppError::new(
+                "unsupported_transport",
+                format!("Unsupported server type: {raw}"),
+            )),
+        }
+    }
+}
+
+/// Connection settings with config, flags and credentials applied. Serialized to hand the
+/// connection to `call-mcp daemon`.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ResolvedConnection {
+    /// Config entry the connection came from, when selected with `--server`.
+    pub server: Option<String>,
+    pub transport: TransportTarget,
+    pub headers: Vec<Header>,
+    /// Whether the Authorization header came from a source that can issue a new token on 401
+    /// (the OAuth token store, a client-credentials grant or a token command).
+    pub refreshable_token: bool,
+    pub oauth: Option<OAuthConfig>,
+    pub token_command: Option<TokenCommand>,
+    pub timeout: Option<u64>,
+    pub connect_timeout: Option<u64>,
+    pub retry: Option<u32>,
+    pub retry_backoff: Option<u64>,
+    pub user_agent: UserAgentPreset,
+    pub log_level: Option<LogLevel>,
+    /// Open sessions through `call-mcp daemon` when it is running.
+    pub use_daemon: bool,
+    /// Environment of the CLI that asked `call-mcp daemon` for the session. Stdio servers,
+    /// token commands and `client_secret_env` use it instead of the daemon's own.
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+impl ResolvedConnection {
+    /// Reads `name` from the connection's environment, or the process's when it has none.
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        match &self.env {
+            Some(env) => env.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
+
+pub async fn resolve_connection(
+    args: &ConnectionArgs,
+    config_path: Option<PathBuf>,
+) -> Result<ResolvedConnection, AppError> {
+    let config = LoadedConfig::load(config_path)?;
+    let mut target = args.url.clone().map(|url| TransportTarget::Http { url });
+    let mut headers: Vec<Header> = Vec::new();
+    let mut token_env = args.token_env.clone();
+    let mut token_command = args.token_command.clone().map(|line| TokenCommand {
+        command: CommandSpec::Shell(line),
+        ttl: None,
+    });
+    let mut timeout = args.timeout;
+    let mut connect_timeout = args.connect_timeout;
+    let mut retry = args.retry;
+    let mut retry_backoff = args.retry_backoff;
+    let mut user_agent = args.user_agent.clone();
+    let mut oauth = None;
+    let mut log_level = None;
+
+    if let Some(server) = &args.server {
+        if config.files.is_empty() {
+            return Err(AppError::new(
+                "config_missing",
+                "Server specified but no config file found",
+            ));
+        }
+
+        let sourced = config.servers.get(server).ok_or_else(|| {
+            AppError::new("server_not_found", format!("Server not found: {server}"))
+        })?;
+        let mut entry = sourced.entry.clone();
+        // Only the selected server is expanded, so unrelated entries may reference
+        // variables that are unset in this environment.
+        entry.expand_env(server)?;
+
+        let kind = match &entry.server_type {
+            Some(raw) => TransportKind::parse(raw)?,
+            None if entry.command.is_some() => TransportKind::Stdio,
+            None => TransportKind::Http,
+        };
+
+        if target.is_none() {
+            target = match kind {
+                TransportKind::Http => entry.url.clone().map(|url| TransportTarget::Http { url }),
+                TransportKind::Sse => entry.url.clone().map(|url| TransportTarget::Sse { url }),
+                TransportKind::Stdio => Some(TransportTarget::Stdio(stdio_command(
+                    server,
+                    &entry,
+                    sourced.source.parent(),
+                )?)),
+            };
+        }
+
+        for (name, value) in &entry.headers {
+            headers.push(Header::new(name, value));
+        }
+
+        // Credentials given on the command line replace the entry's, whichever kind they are.
+        let cli_auth = token_env.is_some()
+            || token_command.is_some()
+            || header_exists(&args.headers, "Authorization");
+        if !cli_auth {
+            token_env = entry.token_env.clone();
+            token_command = entry.token_command.clone().map(|command| TokenCommand {
+                command,
+                ttl: entry.token_command_ttl,
+            });
+        }
+        if timeout.is_none() {
+            timeout = entry.timeout;
+        }
+        if connect_timeout.is_none() {
+            connect_timeout = entry.connect_timeout;
+        }
+        if retry.is_none() {
+            retry = entry.retry;
+        }
+        if retry_backoff.is_none() {
+            retry_backoff = entry.retry_backoff;
+        }
+        oauth = entry.oauth.clone();
+        log_level = entry.log_level;
+        if user_agent.is_none()
+            && let Some(ua_str) = &entry.user_agent
+        {
+            user_agent = Some(ua_str.parse().map_err(|e| {
+                AppError::new(
+                    "invalid_user_agent",
+                    format!("Invalid user_agent value: {e}"),
+                )
+            })?);
+        }
+    }
+
+    headers.extend(args.headers.iter().cloned());
+
+    if let Some(token_env) = token_env {
+        let token = std::env::var(&token_env).map_err(|_| {
+            AppError::new("token_missing", format!("Token env not set: {token_env}"))
+        })?;
+
+        if !header_exists(&headers, "Authorization") {
+            headers.push(Header::new("Authorization", format!("Bearer {token}")));
+        }
+    }
+
+    let mut refreshable_token = false;
+    if let Some(command) = &token_command
+        && !header_exists(&headers, "Authorization")
+    {
+        let token = crate::auth::command_token(command, None, false).await?;
+        headers.push(Header::new("Authorization", format!("Bearer {token}")));
+        refreshable_token = true;
+    }
+
+    let transport = target
+        .ok_or_else(|| AppError::new("missing_connection", "Missing --url or --server value"))?;
+
+    // Default to Chrome if not specified
+    let user_agent = user_agent.unwrap_or_default();
+
+    let mut connection = ResolvedConnection {
+        server: args.server.clone(),
+        transport,
+        headers,
+        refreshable_token,
+        oauth,
+        token_command,
+        timeout,
+        connect_timeout,
+        retry,
+        retry_backoff,
+        user_agent,
+        log_level,
+        use_daemon: !args.no_daemon,
+        env: None,
+    };
+    crate::auth::authorize(&mut connection).await?;
+    Ok(connection)
+}
+
+/// Builds the command of a stdio entry; a relative `cwd` is resolved against `config_dir`,
+/// the directory of the config file the entry came from.
+fn stdio_command(
+    server: &str,
+    entry: &ServerEntry,
+    config_dir: Option<&Path>,
+) -> Result<StdioCommand, AppError> {
+    let command = entry.command.clone().ok_or_else(|| {
+        AppError::new(
+            "missing_connection",
+            format!("Server {server} has type stdio but no command"),
+        )
+    })?;
+
+    Ok(StdioCommand {
+        command,
+        args: entry.args.clone(),
+        env: entry.env.clone(),
+        cwd: entry.cwd.as_ref().map(|cwd| match config_dir {
+            Some(dir) if cwd.is_relative() => dir.join(cwd),
+            _ => cwd.clone(),
+        }),
+    })
+}
+
+/// Replaces `${VAR}` with the variable's value and `${VAR:-default}` with the value, or
+/// `default` when the variable is unset or empty. Text without `${` is returned unchanged.
+///
+/// Variables are read with `lookup`.
+fn expand_env_str(raw: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, AppError> {
+    let mut output = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or_else(|| {
+            AppError::new(
+                "config_parse",
+                format!("Unterminated variable reference in config value: {raw}"),
+            )
+        })?;
+        let reference = &after[..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        if name.is_empty() {
+            return Err(AppError::new(
+                "config_parse",
+                format!("Empty variable reference in config value: {raw}"),
+            ));
+        }
+
+        match (lookup(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => {
+                return Err(AppError::new(
+                    "config_env_missing",
+                    format!("Environment variable not set: {name}"),
+                ));
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+pub(crate) fn header_exists(headers: &[Header], name: &str) -> bool {
+    headers
+        .iter()
+        .any(|header| header.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry(json: &str) -> ServerEntry {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_transport_kind_parse() {
+        assert_eq!(TransportKind::parse("http").unwrap(), TransportKind::Http);
+        assert_eq!(
+            TransportKind::parse("streamable-http").unwrap(),
+            TransportKind::Http
+        );
+        assert_eq!(TransportKind::parse("sse").unwrap(), TransportKind::Sse);
+        assert_eq!(TransportKind::parse("STDIO").unwrap(), TransportKind::Stdio);
+        assert_eq!(
+            TransportKind::parse("websocket").unwrap_err().code(),
+            "unsupported_transport"
+        );
+    }
+
+    #[test]
+    fn test_stdio_command() {
+        let entry = entry(
+            r#"{"type":"stdio","command":"node","args":["server.js"],"env":{"DEBUG":"1"},"cwd":"tools"}"#,
+        );
+        let command = stdio_command("local", &entry, None).unwrap();
+        assert_eq!(command.command, "node");
+        assert_eq!(command.args, vec!["server.js"]);
+        assert_eq!(command.env.get("DEBUG").map(String::as_str), Some("1"));
+        assert_eq!(command.cwd, Some(PathBuf::from("tools")));
+    }
+
+    #[test]
+    fn test_stdio_command_cwd() {
+        let dir = Some(Path::new("/repo"));
+        let relative = entry(r#"{"command":"node","cwd":"tools"}"#);
+        let command = stdio_command("local", &relative, dir).unwrap();
+        assert_eq!(command.cwd, Some(PathBuf::from("/repo/tools")));
+        let absolute = entry(r#"{"command":"node","cwd":"/opt/tools"}"#);
+        let command = stdio_command("local", &absolute, dir).unwrap();
+        assert_eq!(command.cwd, Some(PathBuf::from("/opt/tools")));
+    }
+
+    #[test]
+    fn test_expand_env_str() {
+        let env = HashMap::from([("KEY", "secret"), ("EMPTY", "")]);
+        let expand =
+            |raw: &str| expand_env_str(raw, |name| env.get(name).map(|value| value.to_string()));
+        assert_eq!(expand("Bearer ${KEY}").unwrap(), "Bearer secret");
+        assert_eq!(
+            expand("${UNSET:-https://example.com}/mcp").unwrap(),
+            "https://example.com/mcp"
+        );
+        assert_eq!(expand("${EMPTY:-default}").unwrap(), "default");
+        assert_eq!(expand("plain $HOME").unwrap(), "plain $HOME");
+
+        let err = expand("${UNSET}").unwrap_err();
+        assert_eq!(err.code(), "config_env_missing");
+        assert!(err.message().contains("UNSET"));
+        assert_eq!(expand("${KEY").unwrap_err().code(), "config_parse");
+    }
+
+    #[test]
+    fn test_stdio_command_missing() {
+        let entry = entry(r#"{"type":"stdio"}"#);
+        let err = stdio_command("local", &entry, None).unwrap_err();
+        assert_eq!(err.code(), "missing_connection");
+    }
+}
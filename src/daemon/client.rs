@@ -0,0 +1,167 @@
+use crate::config::{ResolvedConnection, TransportTarget};
+use crate::errors::AppError;
+use crate::mcp_client::{ClientHandlerImpl, Service, TransportUsed, map_init_error};
+use rmcp::ServiceExt;
+use serde_json::{Value, json};
+use std::os::unix::process::CommandExt;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio::net::UnixStream;
+
+use super::protocol::{Reply, Request, read_line, socket_path, write_line};
+
+/// How often `start` and `stop` check whether the daemon is up or gone, and how many times.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const POLL_ATTEMPTS: usize = 50;
+
+/// How long to wait for the daemon to lease a session when no request timeout is set.
+const DEFAULT_LEASE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Opens an MCP session for `connection` through the daemon.
+///
+/// Returns `None` when no daemon is listening, in which case the caller connects directly.
+/// Errors the daemon reports, such as a server that fails to start, are returned as is.
+pub(crate) async fn open_session(
+    connection: &ResolvedConnection,
+    handler: &ClientHandlerImpl,
+) -> Result<Option<(Service, TransportUsed)>, AppError> {
+    let Ok(stream) = connect().await else {
+        return Ok(None);
+    };
+    let mut connection = connection.clone();
+    if let TransportTarget::Stdio(command) = &mut connection.transport
+        && let Ok(current_dir) = std::env::current_dir()
+    {
+        // Start the server relative to this directory rather than the daemon's.
+        command.cwd = Some(match command.cwd.take() {
+            Some(cwd) => current_dir.join(cwd),
+            None => current_dir,
+        });
+    }
+    // Servers and credential helpers see this shell's variables, and sessions opened with
+    // a different environment are not shared.
+    connection.env = Some(
+        std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect(),
+    );
+    // Opening a session may spawn and initialize a server, so it gets the request timeout.
+    let lease_timeout = connection
+        .timeout
+        .map_or(DEFAULT_LEASE_TIMEOUT, Duration::from_millis);
+
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    write_line(&mut writer, &Request::Connect(Box::new(connection))).await?;
+    let reply = tokio::time::timeout(lease_timeout, read_line(&mut reader))
+        .await
+        .map_err(|_| {
+            AppError::new(
+                "daemon_socket",
+                format!(
+                    "Daemon did not open a session within {} ms",
+                    lease_timeout.as_millis()
+                ),
+            )
+        })??;
+    let transport = match reply {
+        Reply::Ok(transport) => serde_json::from_value(transport).map_err(|err| {
+            AppError::new(
+                "daemon_socket",
+                format!("Invalid daemon socket message: {err}"),
+            )
+        })?,
+        Reply::Error(err) => return Err(err),
+    };
+    let service = handler
+        .clone()
+        .serve((reader, writer))
+        .await
+        .map_err(map_init_error)?;
+    Ok(Some((service, transport)))
+}
+
+/// Starts `call-mcp daemon run` in the background and returns its status once it listens.
+/// A daemon that is already running is left as is.
+pub async fn start(idle_timeout: u64) -> Result<Value, AppError> {
+    if let Some(status) = control(Request::Status).await? {
+        return Ok(status);
+    }
+
+    let exe = std::env::current_exe().map_err(|err| {
+        AppError::new("daemon_start", format!("Failed to locate call-mcp: {err}"))
+    })?;
+    let mut child = std::process::Command::new(exe)
+        .args(["daemon", "run", "--idle-timeout", &idle_timeout.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Keep the daemon out of the terminal's process group so Ctrl-C does not stop it.
+        .process_group(0)
+        .spawn()
+        .map_err(|err| AppError::new("daemon_start", format!("Failed to start daemon: {err}")))?;
+
+    for _ in 0..POLL_ATTEMPTS {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if let Some(status) = control(Request::Status).await? {
+            return Ok(status);
+        }
+        if let Ok(Some(exit)) = child.try_wait() {
+            return Err(AppError::new(
+                "daemon_start",
+                format!("Daemon exited with {exit}; run `call-mcp daemon run` to see why"),
+            ));
+        }
+    }
+    Err(AppError::new(
+        "daemon_start",
+        "Daemon did not start listening in time",
+    ))
+}
+
+pub async fn status() -> Result<Value, AppError> {
+    Ok(control(Request::Status)
+        .await?
+        .unwrap_or_else(|| json!({ "running": false })))
+}
+
+/// Asks the daemon to exit and waits until its socket is gone.
+pub async fn stop() -> Result<Value, AppError> {
+    if control(Request::Stop).await?.is_none() {
+        return Ok(json!({ "stopped": false }));
+    }
+    for _ in 0..POLL_ATTEMPTS {
+        if connect().await.is_err() {
+            return Ok(json!({ "stopped": true }));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(AppError::new(
+        "daemon_socket",
+        "Daemon did not stop in time",
+    ))
+}
+
+async fn connect() -> Result<UnixStream, AppError> {
+    let path = socket_path()?;
+    UnixStream::connect(&path).await.map_err(|err| {
+        AppError::new(
+            "daemon_socket",
+            format!("Failed to connect to {}: {err}", path.display()),
+        )
+    })
+}
+
+/// Sends a control request, returning `None` when no daemon is listening.
+async fn control(request: Request) -> Result<Option<Value>, AppError> {
+    let Ok(stream) = connect().await else {
+        return Ok(None);
+    };
+    let (reader, mut writer) = stream.into_split();
+    write_line(&mut writer, &request).await?;
+    match read_line(&mut BufReader::new(reader)).await? {
+        Reply::Ok(value) => Ok(Some(value)),
+        Reply::Error(err) => Err(err),
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg(unix)]
+mod client;
+#[cfg(unix)]
+mod protocol;
+#[cfg(unix)]
+mod proxy;
+#[cfg(unix)]
+mod server;
+#[cfg(not(unix))]
+mod unsupported;
+
+#[cfg(unix)]
+pub(crate) use client::open_session;
+#[cfg(unix)]
+pub use client::{start, status, stop};
+#[cfg(unix)]
+pub use server::run;
+#[cfg(not(unix))]
+pub(crate) use unsupported::open_session;
+#[cfg(not(unix))]
+pub use unsupported::{run, start, status, stop};
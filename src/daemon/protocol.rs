@@ -0,0 +1,68 @@
+use crate::config::ResolvedConnection;
+use crate::errors::AppError;
+use crate::paths;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// First line a client writes to the daemon socket.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum Request {
+    /// Lease a session for this connection. After an `ok` reply the socket carries the MCP
+    /// session, starting with the client's `initialize`.
+    Connect(Box<ResolvedConnection>),
+    Status,
+    Stop,
+}
+
+/// The daemon's one-line reply to a `Request`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum Reply {
+    Ok(Value),
+    Error(AppError),
+}
+
+pub(super) fn socket_path() -> Result<PathBuf, AppError> {
+    paths::daemon_socket_path().ok_or_else(|| {
+        AppError::new(
+            "daemon_socket",
+            "Cannot locate the daemon socket (set XDG_RUNTIME_DIR or HOME)",
+        )
+    })
+}
+
+pub(super) async fn write_line<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &impl Serialize,
+) -> Result<(), AppError> {
+    let mut line = serde_json::to_vec(message)
+        .map_err(|err| AppError::new("json_encode", format!("Failed to encode message: {err}")))?;
+    line.push(b'\n');
+    writer.write_all(&line).await.map_err(socket_error)
+}
+
+pub(super) async fn read_line<R: AsyncBufRead + Unpin, T: DeserializeOwned>(
+    reader: &mut R,
+) -> Result<T, AppError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await.map_err(socket_error)? == 0 {
+        return Err(AppError::new(
+            "daemon_socket",
+            "Daemon socket closed before a reply",
+        ));
+    }
+    serde_json::from_str(&line).map_err(|err| {
+        AppError::new(
+            "daemon_socket",
+            format!("Invalid daemon socket message: {err}"),
+        )
+    })
+}
+
+fn socket_error(err: std::io::Error) -> AppError {
+    AppError::new("daemon_socket", format!("Daemon socket error: {err}"))
+}
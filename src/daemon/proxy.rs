@@ -0,0 +1,121 @@
+use crate::mcp_client::Relay;
+use rmcp::model::{
+    CancelledNotificationParam, ClientNotification, ClientRequest, ErrorData, ServerInfo,
+    ServerResult,
+};
+use rmcp::service::{
+    NotificationContext, Peer, PeerRequestOptions, RequestContext, RoleClient, RoleServer, Service,
+    ServiceError,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Server side of a leased session. Answers `initialize` with the pooled session's server
+/// info and forwards everything else to the upstream server.
+pub(super) struct Proxy {
+    upstream: Peer<RoleClient>,
+    info: ServerInfo,
+    /// Notifications of the pooled session, forwarded to the client.
+    relay: Relay,
+    /// Resources the client subscribed to and has not unsubscribed from yet.
+    subscriptions: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Proxy {
+    pub(super) fn new(upstream: Peer<RoleClient>, info: ServerInfo, relay: Relay) -> Self {
+        Self {
+            upstream,
+            info,
+            relay,
+            subscriptions: Arc::default(),
+        }
+    }
+
+    pub(super) fn subscriptions(&self) -> Arc<Mutex<HashSet<String>>> {
+        self.subscriptions.clone()
+    }
+}
+
+impl Service<RoleServer> for Proxy {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        match &request {
+            ClientRequest::InitializeRequest(_) => {
+                return Ok(ServerResult::InitializeResult(self.info.clone()));
+            }
+            ClientRequest::SubscribeRequest(subscribe) => {
+                let uri = subscribe.params.uri.clone();
+                self.subscriptions.lock().unwrap().insert(uri);
+            }
+            ClientRequest::UnsubscribeRequest(unsubscribe) => {
+                self.subscriptions
+                    .lock()
+                    .unwrap()
+                    .remove(&unsubscribe.params.uri);
+            }
+            _ => {}
+        }
+
+        // The client's `_meta` keeps its progress token, so relayed progress matches it.
+        let options = PeerRequestOptions {
+            timeout: None,
+            meta: Some(context.meta.clone()),
+        };
+        let handle = self
+            .upstream
+            .send_request_with_option(request, options)
+            .await
+            .map_err(upstream_error)?;
+        let request_id = handle.id.clone();
+        tokio::select! {
+            response = handle.await_response() => {
+                // Notifications the server sent before this response were handed to the
+                // relay first (see `server::run`); forward them before the response.
+                self.relay.flush().await;
+                response.map_err(upstream_error)
+            }
+            _ = context.ct.cancelled() => {
+                let _ = self
+                    .upstream
+                    .notify_cancelled(CancelledNotificationParam {
+                        request_id,
+                        reason: None,
+                    })
+                    .await;
+                Err(ErrorData::internal_error("Request cancelled", None))
+            }
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        _context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        match notification {
+            // The pooled session was initialized when it was opened, and cancellations are
+            // sent upstream by `handle_request` with the upstream request id.
+            ClientNotification::InitializedNotification(_)
+            | ClientNotification::CancelledNotification(_) => Ok(()),
+            notification => self
+                .upstream
+                .send_notification(notification)
+                .await
+                .map_err(upstream_error),
+        }
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.info.clone()
+    }
+}
+
+fn upstream_error(err: ServiceError) -> ErrorData {
+    match err {
+        ServiceError::McpError(err) => err,
+        err => ErrorData::internal_error(err.to_string(), None),
+    }
+}
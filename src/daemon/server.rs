@@ -0,0 +1,361 @@
+use crate::config::{ResolvedConnection, TransportTarget};
+use crate::errors::AppError;
+use crate::mcp_client::{McpClient, Relay, Relayed, Service, TransportUsed};
+use rmcp::ServiceExt;
+use rmcp::model::UnsubscribeRequestParam;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::BufReader;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::{Notify, mpsc, oneshot};
+
+use super::protocol::{Reply, Request, read_line, socket_path, write_line};
+use super::proxy::Proxy;
+
+/// Longest time between checks for idle sessions.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Serves the daemon socket until stopped, interrupted or unused for `idle_timeout` seconds.
+///
+/// The daemon runs on a runtime of its own with a single thread, which runs tasks in the
+/// order they are woken. A session handles each server notification in a new task, so the
+/// notifications a server sends before a response reach the relay before the proxy sees
+/// the response, and are forwarded first.
+pub async fn run(idle_timeout: u64) -> Result<(), AppError> {
+    let (done, result) = oneshot::channel();
+    std::thread::spawn(move || {
+        let served = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| {
+                AppError::new(
+                    "daemon_start",
+                    format!("Failed to start the runtime: {err}"),
+                )
+            })
+            .and_then(|runtime| runtime.block_on(listen(idle_timeout)));
+        let _ = done.send(served);
+    });
+    result.await.unwrap_or_else(|_| {
+        Err(AppError::new(
+            "daemon_socket",
+            "Daemon stopped unexpectedly",
+        ))
+    })
+}
+
+async fn listen(idle_timeout: u64) -> Result<(), AppError> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).await.is_ok() {
+        return Err(AppError::new(
+            "daemon_running",
+            format!("A daemon is already listening on {}", path.display()),
+        ));
+    }
+    let listener = bind(&path)?;
+    let daemon = Arc::new(Daemon::new(path.clone(), Duration::from_secs(idle_timeout)));
+
+    let mut terminate = signal(SignalKind::terminate()).map_err(|err| {
+        AppError::new(
+            "daemon_socket",
+            format!("Failed to watch for SIGTERM: {err}"),
+        )
+    })?;
+    let interrupt = tokio::signal::ctrl_c();
+    tokio::pin!(interrupt);
+    let mut sweep = tokio::time::interval(SWEEP_INTERVAL.clamp(
+        Duration::from_secs(1),
+        daemon.idle_timeout.max(Duration::from_secs(1)),
+    ));
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, _)) = accepted {
+                    tokio::spawn(daemon.clone().serve(stream));
+                }
+            }
+            _ = sweep.tick() => {
+                if daemon.sweep() {
+                    break;
+                }
+            }
+            _ = daemon.stop.notified() => break,
+            _ = &mut interrupt => break,
+            _ = terminate.recv() => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&path);
+    daemon.close_idle().await;
+    Ok(())
+}
+
+fn bind(path: &Path) -> Result<UnixListener, AppError> {
+    let map_err = |err: std::io::Error| {
+        AppError::new(
+            "daemon_socket",
+            format!("Failed to listen on {}: {err}", path.display()),
+        )
+    };
+    let parent = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent).map_err(map_err)?;
+    // The socket is created in a directory only this user can enter and made private
+    // before it is moved into place, so nobody else can connect in between.
+    let private = parent.join(format!(".call-mcp-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&private);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .map_err(map_err)?;
+    let bound = private.join("daemon.sock");
+    let listener = UnixListener::bind(&bound).and_then(|listener| {
+        std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+        // Replaces any socket left behind by a daemon that did not exit cleanly.
+        std::fs::rename(&bound, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    listener.map_err(map_err)
+}
+
+struct Daemon {
+    socket: PathBuf,
+    idle_timeout: Duration,
+    pool: Mutex<Pool>,
+    stop: Notify,
+}
+
+struct Pool {
+    /// Initialized sessions waiting for a client, with the time they were returned.
+    idle: Vec<(Session, Instant)>,
+    /// Label and transport of the sessions clients are using, by lease.
+    leased: HashMap<u64, (String, TransportUsed)>,
+    next_lease: u64,
+    /// When a session was last leased or returned.
+    last_used: Instant,
+}
+
+struct Session {
+    /// Serialized connection; only clients with identical settings share a session.
+    key: String,
+    label: String,
+    transport: TransportUsed,
+    service: Service,
+    relay: Relay,
+}
+
+impl Session {
+    async fn open(key: String, connection: ResolvedConnection) -> Result<Self, AppError> {
+        let label = label(&connection);
+        let relay = Relay::default();
+        let client = McpClient::new(connection);
+        let handler = client.handler(false).with_relay(relay.clone());
+        // The client retries failures the daemon reports, so each lease tries only once.
+        let (service, transport) = client.open_session_with(handler).await?;
+        Ok(Self {
+            key,
+            label,
+            transport,
+            service,
+            relay,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.service.peer().is_transport_closed()
+    }
+}
+
+impl Daemon {
+    fn new(socket: PathBuf, idle_timeout: Duration) -> Self {
+        Self {
+            socket,
+            idle_timeout,
+            pool: Mutex::new(Pool {
+                idle: Vec::new(),
+                leased: HashMap::new(),
+                next_lease: 0,
+                last_used: Instant::now(),
+            }),
+            stop: Notify::new(),
+        }
+    }
+
+    async fn serve(self: Arc<Self>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        let reply = match read_line(&mut reader).await {
+            Ok(Request::Connect(connection)) => {
+                return self.lease(*connection, reader, writer).await;
+            }
+            Ok(Request::Status) => Reply::Ok(self.status()),
+            Ok(Request::Stop) => {
+                self.stop.notify_one();
+                Reply::Ok(json!({ "stopped": true }))
+            }
+            Err(err) => Reply::Error(err),
+        };
+        let _ = write_line(&mut writer, &reply).await;
+    }
+
+    /// Serves one client with a session for `connection`, opening one when none is idle, and
+    /// pools the session again once the client disconnects.
+    async fn lease(
+        &self,
+        mut connection: ResolvedConnection,
+        reader: BufReader<OwnedReadHalf>,
+        mut writer: OwnedWriteHalf,
+    ) {
+        connection.use_daemon = false;
+        let (lease, session) = match self.checkout(connection).await {
+            Ok(checked_out) => checked_out,
+            Err(err) => {
+                let _ = write_line(&mut writer, &Reply::Error(err)).await;
+                return;
+            }
+        };
+        if write_line(&mut writer, &Reply::Ok(json!(session.transport)))
+            .await
+            .is_ok()
+        {
+            relay(&session, reader, writer).await;
+        }
+        self.checkin(lease, session);
+    }
+
+    async fn checkout(&self, connection: ResolvedConnection) -> Result<(u64, Session), AppError> {
+        let key = serde_json::to_string(&connection).map_err(|err| {
+            AppError::new("json_encode", format!("Failed to encode connection: {err}"))
+        })?;
+        let idle = {
+            let mut pool = self.pool.lock().unwrap();
+            pool.idle.retain(|(session, _)| !session.is_closed());
+            let position = pool.idle.iter().position(|(session, _)| session.key == key);
+            position.map(|position| pool.idle.swap_remove(position).0)
+        };
+        let session = match idle {
+            Some(session) => session,
+            None => Session::open(key, connection).await?,
+        };
+
+        let mut pool = self.pool.lock().unwrap();
+        let lease = pool.next_lease;
+        pool.next_lease += 1;
+        pool.leased
+            .insert(lease, (session.label.clone(), session.transport));
+        pool.last_used = Instant::now();
+        Ok((lease, session))
+    }
+
+    fn checkin(&self, lease: u64, session: Session) {
+        let mut pool = self.pool.lock().unwrap();
+        pool.leased.remove(&lease);
+        pool.last_used = Instant::now();
+        if !session.is_closed() {
+            pool.idle.push((session, Instant::now()));
+        }
+    }
+
+    /// Closes sessions idle for longer than the timeout, and returns whether the whole daemon
+    /// has been unused that long.
+    fn sweep(&self) -> bool {
+        let mut pool = self.pool.lock().unwrap();
+        pool.idle
+            .retain(|(session, since)| since.elapsed() < self.idle_timeout && !session.is_closed());
+        pool.idle.is_empty()
+            && pool.leased.is_empty()
+            && pool.last_used.elapsed() >= self.idle_timeout
+    }
+
+    async fn close_idle(&self) {
+        let idle = std::mem::take(&mut self.pool.lock().unwrap().idle);
+        for (session, _) in idle {
+            let _ = session.service.cancel().await;
+        }
+    }
+
+    fn status(&self) -> Value {
+        let pool = self.pool.lock().unwrap();
+        let leased = pool.leased.values().map(|(label, transport)| {
+            json!({ "server": label, "transport": transport, "state": "in_use" })
+        });
+        let idle = pool.idle.iter().map(|(session, since)| {
+            json!({
+                "server": session.label,
+                "transport": session.transport,
+                "state": "idle",
+                "idle_seconds": since.elapsed().as_secs(),
+            })
+        });
+        json!({
+            "running": true,
+            "pid": std::process::id(),
+            "socket": self.socket,
+            "idle_timeout": self.idle_timeout.as_secs(),
+            "sessions": leased.chain(idle).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Runs the MCP session between the client and the pooled `session` until the client
+/// disconnects, passing the server's notifications on to the client.
+async fn relay(session: &Session, reader: BufReader<OwnedReadHalf>, writer: OwnedWriteHalf) {
+    let Some(info) = session.service.peer_info().cloned() else {
+        return;
+    };
+    let (sender, mut notifications) = mpsc::unbounded_channel();
+    session.relay.attach(Some(sender));
+    let proxy = Proxy::new(session.service.peer().clone(), info, session.relay.clone());
+    let subscriptions = proxy.subscriptions();
+    if let Ok(client) = proxy.serve((reader, writer)).await {
+        let peer = client.peer().clone();
+        // One task forwards everything in order; `Proxy` flushes it before each response.
+        let forward = tokio::spawn(async move {
+            while let Some(relayed) = notifications.recv().await {
+                match relayed {
+                    Relayed::Notification(notification) => {
+                        if peer.send_notification(notification).await.is_err() {
+                            break;
+                        }
+                    }
+                    Relayed::Flush(flushed) => {
+                        let _ = flushed.send(());
+                    }
+                }
+            }
+        });
+        let _ = client.waiting().await;
+        forward.abort();
+    }
+    session.relay.attach(None);
+
+    // Subscriptions of a client that went away would notify the next one.
+    let uris: Vec<String> = subscriptions.lock().unwrap().drain().collect();
+    for uri in uris {
+        let _ = session
+            .service
+            .peer()
+            .unsubscribe(UnsubscribeRequestParam { uri })
+            .await;
+    }
+}
+
+/// Names a session in `status` by its config entry, or its URL or command line.
+fn label(connection: &ResolvedConnection) -> String {
+    if let Some(server) = &connection.server {
+        return server.clone();
+    }
+    match &connection.transport {
+        TransportTarget::Http { url } | TransportTarget::Sse { url } => url.clone(),
+        TransportTarget::Stdio(command) => std::iter::once(command.command.as_str())
+            .chain(command.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
@@ -0,0 +1,34 @@
+use crate::config::ResolvedConnection;
+use crate::errors::AppError;
+use crate::mcp_client::{ClientHandlerImpl, Service, TransportUsed};
+use serde_json::Value;
+
+fn unsupported() -> AppError {
+    AppError::new(
+        "daemon_unsupported",
+        "call-mcp daemon needs Unix domain sockets and is not available on this platform",
+    )
+}
+
+pub(crate) async fn open_session(
+    _connection: &ResolvedConnection,
+    _handler: &ClientHandlerImpl,
+) -> Result<Option<(Service, TransportUsed)>, AppError> {
+    Ok(None)
+}
+
+pub async fn start(_idle_timeout: u64) -> Result<Value, AppError> {
+    Err(unsupported())
+}
+
+pub async fn run(_idle_timeout: u64) -> Result<(), AppError> {
+    Err(unsupported())
+}
+
+pub async fn status() -> Result<Value, AppError> {
+    Err(unsupported())
+}
+
+pub async fn stop() -> Result<Value, AppError> {
+    Err(unsupported())
+}
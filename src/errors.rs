@@ -1,23 +1,30 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AppError {
-    code: &'static str,
+    code: Cow<'static, str>,
     message: String,
     details: Option<Value>,
 }
 
 impl AppError {
-    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+    pub fn new(code: impl Into<Cow<'static, str>>, message: impl Into<String>) -> Self {
         Self {
-            code,
+            code: code.into(),
             message: message.into(),
             details: None,
         }
     }
 
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
     pub fn code(&self) -> &str {
-        self.code
+        &self.code
     }
 
     pub fn message(&self) -> &str {
@@ -36,3 +43,215 @@ impl std::fmt::Display for AppError {
 }
 
 impl std::error::Error for AppError {}
+
+/// Groups of error codes with a stable process exit code each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    General,
+    Config,
+    Auth,
+    Connection,
+    Protocol,
+    ToolError,
+    NotFound,
+    InvalidParams,
+}
+
+/// Exit code used by clap for command-line usage errors.
+pub const USAGE_EXIT_CODE: u8 = 2;
+
+/// Error codes of each category; a trailing `*` matches a prefix. Codes that are not listed
+/// are `General`.
+const CATEGORY_CODES: [(ErrorCategory, &[&str]); 8] = [
+    (ErrorCategory::General, &["json_encode"]),
+    (
+        ErrorCategory::Config,
+        &[
+            "config_*",
+            "server_not_found",
+            "missing_connection",
+            "unsupported_transport",
+            "invalid_user_agent",
+            "invalid_header",
+            "invalid_url",
+            "notification_file",
+            "output_write",
+        ],
+    ),
+    (ErrorCategory::Auth, &["auth_*", "token_*"]),
+    (
+        ErrorCategory::Connection,
+        &[
+            "mcp_init",
+            "sse_connect",
+            "http_client",
+            "process_spawn",
+            "connection_closed",
+            "daemon_socket",
+            "daemon_start",
+            "daemon_running",
+            "daemon_unsupported",
+        ],
+    ),
+    (
+        ErrorCategory::Protocol,
+        &[
+            "mcp_service",
+            "missing_server_info",
+            "unsupported_capability",
+            "invalid_schema",
+            "invalid_structured_content",
+            "structured_missing",
+            "invalid_base64",
+        ],
+    ),
+    (ErrorCategory::ToolError, &["tool_error"]),
+    (ErrorCategory::NotFound, &["not_found"]),
+    (
+        ErrorCategory::InvalidParams,
+        &[
+            "invalid_params",
+            "invalid_json",
+            "params_file",
+            "invalid_uri_template",
+            "invalid_pattern",
+        ],
+    ),
+];
+
+impl ErrorCategory {
+    pub const ALL: [ErrorCategory; 8] = [
+        ErrorCategory::General,
+        ErrorCategory::Config,
+        ErrorCategory::Auth,
+        ErrorCategory::Connection,
+        ErrorCategory::Protocol,
+        ErrorCategory::ToolError,
+        ErrorCategory::NotFound,
+        ErrorCategory::InvalidParams,
+    ];
+
+    pub fn of(code: &str) -> Self {
+        Self::listed(code).unwrap_or(ErrorCategory::General)
+    }
+
+    /// Category that lists `code`, if any.
+    fn listed(code: &str) -> Option<Self> {
+        CATEGORY_CODES
+            .iter()
+            .find(|(_, patterns)| {
+                patterns
+                    .iter()
+                    .any(|pattern| match pattern.strip_suffix('*') {
+                        Some(prefix) => code.starts_with(prefix),
+                        None => code == *pattern,
+                    })
+            })
+            .map(|(category, _)| *category)
+    }
+
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorCategory::General => 1,
+            ErrorCategory::Config => 3,
+            ErrorCategory::Auth => 4,
+            ErrorCategory::Connection => 5,
+            ErrorCategory::Protocol => 6,
+            ErrorCategory::ToolError => 7,
+            ErrorCategory::NotFound => 8,
+            ErrorCategory::InvalidParams => 9,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorCategory::General => "general",
+            ErrorCategory::Config => "config",
+            ErrorCategory::Auth => "auth",
+            ErrorCategory::Connection => "connection",
+            ErrorCategory::Protocol => "protocol",
+            ErrorCategory::ToolError => "tool_error",
+            ErrorCategory::NotFound => "not_found",
+            ErrorCategory::InvalidParams => "invalid_params",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ErrorCategory::General => "Unexpected or uncategorized error",
+            ErrorCategory::Config => "Config file or connection settings are missing or invalid",
+            ErrorCategory::Auth => "Credentials are missing, rejected or could not be obtained",
+            ErrorCategory::Connection => "The server could not be reached or started",
+            ErrorCategory::Protocol => "The server returned an MCP error or an unexpected result",
+            ErrorCategory::ToolError => "The tool ran and reported an error (isError)",
+            ErrorCategory::NotFound => "The requested tool, resource or prompt does not exist",
+            ErrorCategory::InvalidParams => "Parameters are not valid JSON or fail validation",
+        }
+    }
+
+    /// Error codes listed for this category (prefix patterns end with `*`).
+    pub fn codes(self) -> &'static [&'static str] {
+        CATEGORY_CODES
+            .iter()
+            .find(|(category, _)| *category == self)
+            .map_or(&[], |(_, codes)| codes)
+    }
+}
+
+impl AppError {
+    pub fn exit_code(&self) -> u8 {
+        ErrorCategory::of(&self.code).exit_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_category() {
+        // Each listed code, and any code matching a prefix pattern, maps to the category that
+        // lists it rather than to an earlier one.
+        for category in ErrorCategory::ALL {
+            for pattern in category.codes() {
+                let code = match pattern.strip_suffix('*') {
+                    Some(prefix) => format!("{prefix}example"),
+                    None => pattern.to_string(),
+                };
+                assert_eq!(ErrorCategory::of(&code), category, "{code}");
+            }
+        }
+        assert_eq!(ErrorCategory::of("unknown_code"), ErrorCategory::General);
+        assert_eq!(ErrorCategory::of("config"), ErrorCategory::General);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let exit_code = |code: &'static str| AppError::new(code, "message").exit_code();
+        assert_eq!(exit_code("json_encode"), 1);
+        assert_eq!(exit_code("unknown_code"), 1);
+        assert_eq!(exit_code("config_parse"), 3);
+        assert_eq!(exit_code("server_not_found"), 3);
+        assert_eq!(exit_code("auth_required"), 4);
+        assert_eq!(exit_code("token_command"), 4);
+        assert_eq!(exit_code("mcp_init"), 5);
+        assert_eq!(exit_code("daemon_socket"), 5);
+        assert_eq!(exit_code("mcp_service"), 6);
+        assert_eq!(exit_code("tool_error"), 7);
+        assert_eq!(exit_code("not_found"), 8);
+        assert_eq!(exit_code("invalid_params"), 9);
+        assert_eq!(
+            AppError::new(String::from("invalid_json"), "message").exit_code(),
+            9
+        );
+
+        let mut exit_codes: Vec<u8> = ErrorCategory::ALL
+            .iter()
+            .map(|category| category.exit_code())
+            .chain([0, USAGE_EXIT_CODE])
+            .collect();
+        exit_codes.sort_unstable();
+        exit_codes.dedup();
+        assert_eq!(exit_codes.len(), ErrorCategory::ALL.len() + 2);
+    }
+}
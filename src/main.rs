@@ -1,45 +1,125 @@
+mod aggregate;
+mod auth;
 mod cli;
 mod config;
+mod daemon;
 mod errors;
 mod mcp_client;
 mod output;
+mod paths;
 mod types;
 mod user_agent;
 
-use clap::Parser;
-use errors::AppError;
-use serde_json::Value;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use errors::{AppError, ErrorCategory, USAGE_EXIT_CODE};
+use output::OutputFormat;
+use rmcp::model::Reference;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::io::Read;
+use std::path::PathBuf;
 use std::process::ExitCode;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    match run().await {
+    let cli = cli::Cli::parse();
+    let format = cli.format;
+
+    match run(cli).await {
         Ok(RunOutput::Json(result)) => {
-            output::print_ok(result);
+            output::print_ok(&result, format);
             ExitCode::SUCCESS
         }
-        Ok(RunOutput::Text(result)) => {
-            println!("{result}");
-            ExitCode::SUCCESS
+        Ok(RunOutput::Streamed) => ExitCode::SUCCESS,
+        Ok(RunOutput::Content {
+            text,
+            result,
+            is_error,
+        }) => {
+            if format == OutputFormat::Text {
+                if is_error {
+                    let err = AppError::new("tool_error", text);
+                    output::print_error(&err, format);
+                    return ExitCode::from(err.exit_code());
+                }
+                println!("{text}");
+            } else {
+                output::print_ok(&result, format);
+            }
+            if is_error {
+                ExitCode::from(ErrorCategory::ToolError.exit_code())
+            } else {
+                ExitCode::SUCCESS
+            }
         }
         Err(err) => {
-            output::print_error(&err);
-            ExitCode::from(1)
+            output::print_error(&err, format);
+            ExitCode::from(err.exit_code())
         }
     }
 }
 
 enum RunOutput {
     Json(Value),
-    Text(String),
+    /// Tool, resource or prompt content: flattened text for `--format text`, the full MCP
+    /// result otherwise.
+    Content {
+        text: String,
+        result: Value,
+        is_error: bool,
+    },
+    /// Results were already printed while the command ran.
+    Streamed,
 }
 
-async fn run() -> Result<RunOutput, AppError> {
-    let cli = cli::Cli::parse();
+impl RunOutput {
+    fn content(text: String, result: impl Serialize, is_error: bool) -> Result<Self, AppError> {
+        Ok(RunOutput::Content {
+            text,
+            result: to_json(result)?,
+            is_error,
+        })
+    }
+}
+
+fn to_json(value: impl Serialize) -> Result<Value, AppError> {
+    serde_json::to_value(value)
+        .map_err(|err| AppError::new("json_encode", format!("Failed to encode result: {err}")))
+}
 
-    match cli.command {
+async fn run(cli: cli::Cli) -> Result<RunOutput, AppError> {
+    if cli.exit_code_map {
+        return Ok(RunOutput::Json(exit_code_map()));
+    }
+    let Some(command) = cli.command else {
+        cli::Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit();
+    };
+
+    match command {
         cli::Command::ListTools(args) => {
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
+            list_command(mcp_client::ListKind::Tools, args, cli.config).await
+        }
+        cli::Command::SearchTools(args) => {
+            if args.all_servers {
+                let (query, limit) = (args.query.as_str(), args.limit);
+                let mut output = aggregate::across_servers(
+                    &args.connection,
+                    cli.config,
+                    args.require_capability
+                        .then_some(mcp_client::CapabilityKind::Tools),
+                    "tools",
+                    None,
+                    |client| async move { client.search_tools(query, limit).await },
+                )
+                .await?;
+                aggregate::keep_top_scored(&mut output, "tools", args.limit);
+                return Ok(RunOutput::Json(output));
+            }
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
             let client = mcp_client::McpClient::new(connection);
             if args.require_capability {
                 client
@@ -47,80 +127,264 @@ async fn run() -> Result<RunOutput, AppError> {
                     .await?;
             }
             client
-                .list_tools(args.cursor, args.name.as_deref(), args.short)
+                .search_tools(&args.query, args.limit)
                 .await
                 .map(RunOutput::Json)
         }
         cli::Command::ListResources(args) => {
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
-            let client = mcp_client::McpClient::new(connection);
-            if args.require_capability {
-                client
-                    .ensure_capability(mcp_client::CapabilityKind::Resources)
-                    .await?;
-            }
-            client
-                .list_resources(args.cursor, args.name.as_deref(), args.short)
-                .await
-                .map(RunOutput::Json)
+            list_command(mcp_client::ListKind::Resources, args, cli.config).await
+        }
+        cli::Command::ListResourceTemplates(args) => {
+            list_command(mcp_client::ListKind::ResourceTemplates, args, cli.config).await
         }
         cli::Command::ListPrompts(args) => {
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
-            let client = mcp_client::McpClient::new(connection);
+            list_command(mcp_client::ListKind::Prompts, args, cli.config).await
+        }
+        cli::Command::CallTool(mut args) => {
+            let tool = apply_server_from_target(&mut args.connection, &args.tool);
+            let mut connection = config::resolve_connection(&args.connection, cli.config).await?;
+            if args.log_level.is_some() {
+                connection.log_level = args.log_level;
+            }
+            let arguments = mcp_client::Arguments::new(parse_json_arg(args.params)?, args.args);
+            let notifications =
+                mcp_client::NotificationSink::new(cli.notifications, cli.notification_format)?;
+            let client =
+                mcp_client::McpClient::new(connection).with_notifications(Arc::new(notifications));
             if args.require_capability {
                 client
-                    .ensure_capability(mcp_client::CapabilityKind::Prompts)
+                    .ensure_capability(mcp_client::CapabilityKind::Tools)
                     .await?;
             }
-            client
-                .list_prompts(args.cursor, args.name.as_deref(), args.short)
-                .await
-                .map(RunOutput::Json)
+            if args.structured {
+                return client
+                    .call_tool_structured(tool, arguments, !args.no_validate)
+                    .await
+                    .map(RunOutput::Json);
+            }
+            let mut result = client
+                .call_tool(tool.clone(), arguments, !args.no_validate)
+                .await?;
+            if let Some(dir) = &args.output_dir {
+                mcp_client::OutputDir::new(dir, &tool)?.save_call_tool(&mut result)?;
+            }
+            let text = mcp_client::call_tool_result_to_text(&result);
+            let is_error = result.is_error.unwrap_or(false);
+            // Always report `isError` so scripts need not treat a missing field as false.
+            result.is_error = Some(is_error);
+            RunOutput::content(text, result, is_error)
         }
-        cli::Command::CallTool(mut args) => {
-            let tool = apply_server_from_target(&mut args.connection, &args.tool);
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
-            let params = parse_json_arg(args.params)?;
+        cli::Command::ReadResource(mut args) => {
+            let uri = match args.template.take() {
+                Some(template) => {
+                    let template = apply_server_from_target(&mut args.connection, &template);
+                    let params = parse_json_arg(args.params.take())?;
+                    let params = mcp_client::Arguments::new(params, args.args).into_value()?;
+                    mcp_client::expand_resource_template(&template, params)?
+                }
+                None => {
+                    let uri = args.uri.take().unwrap_or_default();
+                    apply_server_from_target(&mut args.connection, &uri)
+                }
+            };
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
             let client = mcp_client::McpClient::new(connection);
             if args.require_capability {
                 client
-                    .ensure_capability(mcp_client::CapabilityKind::Tools)
+                    .ensure_capability(mcp_client::CapabilityKind::Resources)
                     .await?;
             }
-            client.call_tool(tool, params).await.map(RunOutput::Text)
+            let mut result = client.read_resource(uri).await?;
+            if let Some(dir) = &args.output_dir {
+                mcp_client::OutputDir::new(dir, "resource")?.save_read_resource(&mut result)?;
+            }
+            let text = mcp_client::read_resource_result_to_text(&result);
+            RunOutput::content(text, result, false)
         }
-        cli::Command::ReadResource(mut args) => {
+        cli::Command::WatchResource(mut args) => {
             let uri = apply_server_from_target(&mut args.connection, &args.uri);
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
-            let client = mcp_client::McpClient::new(connection);
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
+            let notifications =
+                mcp_client::NotificationSink::new(cli.notifications, cli.notification_format)?;
+            let client =
+                mcp_client::McpClient::new(connection).with_notifications(Arc::new(notifications));
             if args.require_capability {
                 client
                     .ensure_capability(mcp_client::CapabilityKind::Resources)
                     .await?;
             }
-            client.read_resource(uri).await.map(RunOutput::Text)
+            let format = cli.format;
+            client
+                .watch_resource(uri, args.count, |result| {
+                    if format == OutputFormat::Text {
+                        println!("{}", mcp_client::read_resource_result_to_text(&result));
+                    } else {
+                        output::print_ok(&to_json(result)?, format);
+                    }
+                    Ok(())
+                })
+                .await?;
+            Ok(RunOutput::Streamed)
         }
         cli::Command::GetPrompt(mut args) => {
             let prompt_id = apply_server_from_target(&mut args.connection, &args.prompt_id);
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
-            let params = parse_json_arg(args.params)?;
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
+            let params =
+                mcp_client::Arguments::new(parse_json_arg(args.params)?, args.args).into_value()?;
             let client = mcp_client::McpClient::new(connection);
             if args.require_capability {
                 client
                     .ensure_capability(mcp_client::CapabilityKind::Prompts)
                     .await?;
             }
-            client
-                .get_prompt(prompt_id, params)
-                .await
-                .map(RunOutput::Text)
+            let mut result = client.get_prompt(prompt_id.clone(), params).await?;
+            if let Some(dir) = &args.output_dir {
+                mcp_client::OutputDir::new(dir, &prompt_id)?.save_prompt(&mut result)?;
+            }
+            let text = mcp_client::prompt_messages_to_text(&result.messages);
+            RunOutput::content(text, result, false)
         }
         cli::Command::GetInfo(args) => {
-            let connection = config::resolve_connection(&args.connection, cli.config)?;
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
             let client = mcp_client::McpClient::new(connection);
             client.get_info().await.map(RunOutput::Json)
         }
+        cli::Command::Complete(mut args) => {
+            let name = apply_server_from_target(&mut args.connection, &args.name);
+            let reference = match args.kind {
+                cli::CompleteKind::Prompt => Reference::for_prompt(name),
+                cli::CompleteKind::Resource => Reference::for_resource(name),
+            };
+            let connection = config::resolve_connection(&args.connection, cli.config).await?;
+            let client = mcp_client::McpClient::new(connection);
+            if args.require_capability {
+                client
+                    .ensure_capability(mcp_client::CapabilityKind::Completions)
+                    .await?;
+            }
+            client
+                .complete(reference, args.argument, args.partial)
+                .await
+                .map(RunOutput::Json)
+        }
+        cli::Command::Auth(args) => match args.command {
+            cli::AuthCommand::Login(args) => {
+                let connection = config::resolve_connection(&args.connection, cli.config).await?;
+                let client_secret = args
+                    .client_secret_env
+                    .map(|name| {
+                        std::env::var(&name).map_err(|_| {
+                            AppError::new(
+                                "token_missing",
+                                format!("Client secret env not set: {name}"),
+                            )
+                        })
+                    })
+                    .transpose()?;
+                let options = auth::LoginOptions {
+                    client_id: args.client_id,
+                    client_secret,
+                    scopes: args.scopes,
+                    redirect_port: args.redirect_port,
+                    open_browser: !args.no_browser,
+                };
+                auth::login(&connection, options).await.map(RunOutput::Json)
+            }
+            cli::AuthCommand::Logout(args) => {
+                let connection = config::resolve_connection(&args.connection, cli.config).await?;
+                auth::logout(&connection).map(RunOutput::Json)
+            }
+        },
+        cli::Command::Config(args) => match args.command {
+            cli::ConfigCommand::Show(args) => {
+                config::show_config(cli.config, args.resolved).map(RunOutput::Json)
+            }
+            cli::ConfigCommand::Import(args) => {
+                let options = config::ImportOptions {
+                    from: args.from,
+                    source: args.source,
+                    target: cli.config.unwrap_or_else(config::default_config_path),
+                    overwrite: args.overwrite,
+                    dry_run: args.dry_run,
+                };
+                config::import_servers(options).map(RunOutput::Json)
+            }
+        },
+        cli::Command::Daemon(args) => match args.command {
+            cli::DaemonCommand::Start(args) => {
+                daemon::start(args.idle_timeout).await.map(RunOutput::Json)
+            }
+            cli::DaemonCommand::Run(args) => {
+                daemon::run(args.idle_timeout).await?;
+                Ok(RunOutput::Streamed)
+            }
+            cli::DaemonCommand::Status => daemon::status().await.map(RunOutput::Json),
+            cli::DaemonCommand::Stop => daemon::stop().await.map(RunOutput::Json),
+        },
+    }
+}
+
+async fn list_command(
+    kind: mcp_client::ListKind,
+    args: cli::ListArgs,
+    config_path: Option<PathBuf>,
+) -> Result<RunOutput, AppError> {
+    let mut pagination = args.pagination();
+    let name = args.name_filter()?;
+    if args.all_servers {
+        // Cursors belong to one server, so every page of every server is fetched.
+        pagination.all = true;
+        let (pagination, filter, short) = (&pagination, name.as_ref(), args.short);
+        let mut output = aggregate::across_servers(
+            &args.connection,
+            config_path,
+            args.require_capability.then(|| kind.capability()),
+            kind.items_key(short),
+            args.name.as_deref(),
+            |client| async move { client.list(kind, pagination, filter, short).await },
+        )
+        .await?;
+        // Each server stops at the limit; the merged listing is held to it as well.
+        if let Some(limit) = args.limit {
+            aggregate::keep_first(&mut output, kind.items_key(short), limit);
+        }
+        return Ok(RunOutput::Json(output));
     }
+
+    let connection = config::resolve_connection(&args.connection, config_path).await?;
+    let client = mcp_client::McpClient::new(connection);
+    if args.require_capability {
+        client.ensure_capability(kind.capability()).await?;
+    }
+    client
+        .list(kind, &pagination, name.as_ref(), args.short)
+        .await
+        .map(RunOutput::Json)
+}
+
+fn exit_code_map() -> Value {
+    let mut entries = vec![
+        json!({
+            "exit_code": 0,
+            "category": "success",
+            "description": "Command succeeded",
+        }),
+        json!({
+            "exit_code": USAGE_EXIT_CODE,
+            "category": "usage",
+            "description": "Invalid command-line arguments",
+        }),
+    ];
+    entries.extend(ErrorCategory::ALL.iter().map(|category| {
+        json!({
+            "exit_code": category.exit_code(),
+            "category": category.name(),
+            "description": category.description(),
+            "codes": category.codes(),
+        })
+    }));
+    entries.sort_by_key(|entry| entry["exit_code"].as_u64());
+    Value::Array(entries)
 }
 
 fn apply_server_from_target(connection: &mut cli::ConnectionArgs, target: &str) -> String {
@@ -149,12 +413,26 @@ fn split_server_target(target: &str) -> Option<(String, String)> {
     Some((server.to_string(), rest.to_string()))
 }
 
+/// Parses a JSON flag given inline, as `@<path>` to read a file or as `-` to read stdin.
 fn parse_json_arg(raw: Option<String>) -> Result<Option<Value>, AppError> {
     let Some(raw) = raw else {
         return Ok(None);
     };
 
-    let value = serde_json::from_str(&raw)
+    let json = if raw == "-" {
+        let mut json = String::new();
+        std::io::stdin()
+            .read_to_string(&mut json)
+            .map_err(|err| AppError::new("params_file", format!("Failed to read stdin: {err}")))?;
+        json
+    } else if let Some(path) = raw.strip_prefix('@') {
+        std::fs::read_to_string(path)
+            .map_err(|err| AppError::new("params_file", format!("Failed to read {path}: {err}")))?
+    } else {
+        raw
+    };
+    // Editors on Windows often save JSON with a byte order mark.
+    let value = serde_json::from_str(json.trim_start_matches('\u{feff}'))
         .map_err(|err| AppError::new("invalid_json", format!("Invalid JSON: {err}")))?;
     Ok(Some(value))
 }
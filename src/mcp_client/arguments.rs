@@ -0,0 +1,168 @@
+use crate::errors::AppError;
+use rmcp::model::JsonObject;
+use serde_json::Value;
+use std::str::FromStr;
+
+use super::util::value_to_object;
+
+/// One `--arg` pair: `key=value` for text, `key:=<json>` for a JSON value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgPair {
+    key: String,
+    value: ArgValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ArgValue {
+    Text(String),
+    Json(String),
+}
+
+impl FromStr for ArgPair {
+    type Err = String;
+
+    fn from_str(pair: &str) -> Result<Self, Self::Err> {
+        let Some((key, value)) = pair.split_once('=') else {
+            return Err(format!("expected KEY=VALUE or KEY:=JSON, got `{pair}`"));
+        };
+        let (key, value) = match key.strip_suffix(':') {
+            Some(key) => (key, ArgValue::Json(value.to_string())),
+            None => (key, ArgValue::Text(value.to_string())),
+        };
+        if key.is_empty() {
+            return Err(format!("missing key in `{pair}`"));
+        }
+        Ok(ArgPair {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+/// Arguments from `--params` with `--arg` pairs merged in; pairs override params keys.
+#[derive(Debug, Clone, Default)]
+pub struct Arguments {
+    params: Option<Value>,
+    pairs: Vec<ArgPair>,
+}
+
+impl Arguments {
+    pub fn new(params: Option<Value>, pairs: Vec<ArgPair>) -> Self {
+        Self { params, pairs }
+    }
+
+    /// Whether any `key=value` pair could be coerced with the tool's input schema.
+    pub(crate) fn has_text(&self) -> bool {
+        self.pairs
+            .iter()
+            .any(|pair| matches!(pair.value, ArgValue::Text(_)))
+    }
+
+    /// Merged arguments, keeping `key=value` pairs as strings.
+    pub fn into_value(self) -> Result<Option<Value>, AppError> {
+        Ok(self.into_object(None)?.map(Value::Object))
+    }
+
+    /// Merged arguments, converting `key=value` text to the type `schema` declares for the
+    /// key.
+    pub(crate) fn into_object(
+        self,
+        schema: Option<&JsonObject>,
+    ) -> Result<Option<JsonObject>, AppError> {
+        let mut object = value_to_object(self.params, "params")?;
+        if self.pairs.is_empty() {
+            return Ok(object);
+        }
+
+        let properties = schema
+            .and_then(|schema| schema.get("properties"))
+            .and_then(Value::as_object);
+        let merged = object.get_or_insert_default();
+        for ArgPair { key, value } in self.pairs {
+            let value = match value {
+                ArgValue::Json(json) => serde_json::from_str(&json).map_err(|err| {
+                    AppError::new(
+                        "invalid_json",
+                        format!("Invalid JSON for --arg {key}: {err}"),
+                    )
+                })?,
+                ArgValue::Text(text) => {
+                    coerce(text, properties.and_then(|properties| properties.get(&key)))
+                }
+            };
+            merged.insert(key, value);
+        }
+        Ok(object)
+    }
+}
+
+/// Parses `text` as JSON when the result has a type `property` allows. It stays a string when
+/// the property accepts strings, declares no type, or the parsed value does not fit.
+fn coerce(text: String, property: Option<&Value>) -> Value {
+    let types: Vec<&str> = match property.and_then(|property| property.get("type")) {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if types.contains(&"string") {
+        return Value::String(text);
+    }
+    let Ok(parsed) = serde_json::from_str::<Value>(&text) else {
+        return Value::String(text);
+    };
+    let fits = |kind: &&str| match *kind {
+        "integer" => parsed.is_i64() || parsed.is_u64(),
+        "number" => parsed.is_number(),
+        "boolean" => parsed.is_boolean(),
+        "array" => parsed.is_array(),
+        "object" => parsed.is_object(),
+        "null" => parsed.is_null(),
+        _ => false,
+    };
+    if types.iter().any(fits) {
+        parsed
+    } else {
+        Value::String(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_and_coerce() {
+        let pairs = [
+            "url=https://a.test",
+            "depth=2",
+            "loud=true",
+            "tags:=[1]",
+            "id=7",
+        ]
+        .map(|pair| pair.parse().unwrap())
+        .to_vec();
+        let schema = json!({"properties": {
+            "depth": {"type": "integer"},
+            "loud": {"type": ["boolean", "null"]},
+            "id": {"type": "string"},
+        }});
+        let arguments = Arguments::new(Some(json!({"depth": 1, "keep": "x"})), pairs);
+
+        let coerced = arguments
+            .clone()
+            .into_object(schema.as_object())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Value::Object(coerced),
+            json!({"depth": 2, "keep": "x", "url": "https://a.test", "loud": true, "tags": [1], "id": "7"})
+        );
+        assert_eq!(arguments.into_value().unwrap().unwrap()["depth"], "2");
+
+        assert!("novalue".parse::<ArgPair>().is_err());
+        assert!(":=1".parse::<ArgPair>().is_err());
+        let bad = Arguments::new(None, vec!["a:={".parse().unwrap()]);
+        assert_eq!(bad.into_value().unwrap_err().code(), "invalid_json");
+    }
+}
@@ -1,37 +1,205 @@
 use crate::errors::AppError;
-use rmcp::model::CallToolRequestParam;
+use rmcp::model::{CallToolRequestParam, CallToolResult, JsonObject, Tool};
 use serde_json::Value;
 use std::borrow::Cow;
 
 use super::McpClient;
+use super::arguments::Arguments;
+use super::core::Service;
+use super::schema::validate_against_schema;
 use super::text::call_tool_result_to_text;
-use super::util::{map_service_error, value_to_object};
+use super::util::map_service_error;
 
 impl McpClient {
-    pub async fn call_tool(&self, tool: String, params: Option<Value>) -> Result<String, AppError> {
+    /// Calls a tool, first checking `arguments` against its `inputSchema` when `validate` is
+    /// set.
+    pub async fn call_tool(
+        &self,
+        tool: String,
+        arguments: Arguments,
+        validate: bool,
+    ) -> Result<CallToolResult, AppError> {
         self.retry("call-tool", || {
             let tool = tool.clone();
-            let params = params.clone();
+            let arguments = arguments.clone();
             async move {
                 let service = self.connect(true).await?;
-                let arguments = value_to_object(params, "params")?;
-                let result = service
-                    .peer()
-                    .call_tool(CallToolRequestParam {
-                        name: Cow::Owned(tool),
-                        arguments,
-                        task: None,
-                    })
-                    .await
-                    .map_err(map_service_error)?;
+                let result = self.call(&service, tool, arguments, validate).await;
                 let _ = service.cancel().await;
-                let text = call_tool_result_to_text(&result);
-                if result.is_error.unwrap_or(false) {
-                    return Err(AppError::new("tool_error", text));
-                }
-                Ok(text)
+                result
             }
         })
         .await
     }
+
+    /// Calls a tool and returns its `structuredContent`, validated against the tool's
+    /// `outputSchema` when one is declared.
+    pub async fn call_tool_structured(
+        &self,
+        tool: String,
+        arguments: Arguments,
+        validate: bool,
+    ) -> Result<Value, AppError> {
+        self.retry("call-tool", || {
+            let tool = tool.clone();
+            let arguments = arguments.clone();
+            async move {
+                let service = self.connect(true).await?;
+                let result = self
+                    .call_structured(&service, tool, arguments, validate)
+                    .await;
+                let _ = service.cancel().await;
+                result
+            }
+        })
+        .await
+    }
+
+    async fn call(
+        &self,
+        service: &Service,
+        tool: String,
+        arguments: Arguments,
+        validate: bool,
+    ) -> Result<CallToolResult, AppError> {
+        let arguments = if validate || arguments.has_text() {
+            let definition = self.tool_definition(service, &tool).await?;
+            match prepare_arguments(&definition, arguments.clone(), validate) {
+                // A definition cached by an earlier run may predate a schema change.
+                Err(err)
+                    if err.code() == "invalid_params" && !self.is_tool_definition_fresh(&tool) =>
+                {
+                    let definition = self.fetch_tool_definition(service, &tool).await?;
+                    prepare_arguments(&definition, arguments, validate)?
+                }
+                result => result?,
+            }
+        } else {
+            arguments.into_object(None)?
+        };
+        service
+            .peer()
+            .call_tool(CallToolRequestParam {
+                name: Cow::Owned(tool),
+                arguments,
+                task: None,
+            })
+            .await
+            .map_err(map_service_error)
+    }
+
+    async fn call_structured(
+        &self,
+        service: &Service,
+        tool: String,
+        arguments: Arguments,
+        validate: bool,
+    ) -> Result<Value, AppError> {
+        let result = self
+            .call(service, tool.clone(), arguments, validate)
+            .await?;
+        if result.is_error.unwrap_or(false) {
+            return Err(AppError::new(
+                "tool_error",
+                call_tool_result_to_text(&result),
+            ));
+        }
+        let structured = result.structured_content.ok_or_else(|| {
+            AppError::new(
+                "structured_missing",
+                format!("Tool '{tool}' returned no structured content"),
+            )
+        })?;
+
+        let definition = self.tool_definition(service, &tool).await?;
+        match validate_structured(&definition, &structured) {
+            Err(err)
+                if err.code() == "invalid_structured_content"
+                    && !self.is_tool_definition_fresh(&tool) =>
+            {
+                let definition = self.fetch_tool_definition(service, &tool).await?;
+                validate_structured(&definition, &structured)?
+            }
+            result => result?,
+        }
+        Ok(structured)
+    }
+}
+
+/// Converts `arguments` to the tool's parameter types and, when `validate` is set, checks
+/// them against its `inputSchema`.
+fn prepare_arguments(
+    tool: &Tool,
+    arguments: Arguments,
+    validate: bool,
+) -> Result<Option<JsonObject>, AppError> {
+    let arguments = arguments.into_object(Some(tool.input_schema.as_ref()))?;
+    if validate {
+        validate_params(tool, arguments.as_ref())?;
+    }
+    Ok(arguments)
+}
+
+fn validate_structured(tool: &Tool, structured: &Value) -> Result<(), AppError> {
+    match &tool.output_schema {
+        Some(schema) => validate_against_schema(
+            schema,
+            structured,
+            "invalid_structured_content",
+            "Structured content",
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Checks tool arguments against the tool's `inputSchema`; no arguments count as `{}`.
+fn validate_params(tool: &Tool, arguments: Option<&JsonObject>) -> Result<(), AppError> {
+    let arguments = Value::Object(arguments.cloned().unwrap_or_default());
+    match validate_against_schema(&tool.input_schema, &arguments, "invalid_params", "Params") {
+        // A schema that cannot be compiled is left for the server to enforce.
+        Err(err) if err.code() == "invalid_schema" => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_validate_params() {
+        let tool = |schema: Value| {
+            Tool::new(
+                "scrape",
+                "Scrape a page",
+                Arc::new(schema.as_object().unwrap().clone()),
+            )
+        };
+        let scrape = tool(json!({
+            "type": "object",
+            "properties": { "url": { "type": "string" }, "depth": { "type": "integer" } },
+            "required": ["url"]
+        }));
+        let arguments = |value: Value| value.as_object().cloned();
+
+        assert!(validate_params(&scrape, arguments(json!({"url": "a"})).as_ref()).is_ok());
+        let err = validate_params(&scrape, arguments(json!({"depth": "2"})).as_ref()).unwrap_err();
+        assert_eq!(err.code(), "invalid_params");
+        let paths: Vec<&Value> = err.details().unwrap()["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| &error["path"])
+            .collect();
+        assert_eq!(paths, [&json!(""), &json!("/depth")]);
+        assert_eq!(
+            validate_params(&scrape, None).unwrap_err().code(),
+            "invalid_params"
+        );
+
+        let broken = tool(json!({"type": "object", "properties": {"url": {"type": 5}}}));
+        assert!(validate_params(&broken, None).is_ok());
+    }
 }
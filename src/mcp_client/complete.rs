@@ -0,0 +1,39 @@
+use crate::errors::AppError;
+use rmcp::model::{ArgumentInfo, CompleteRequestParam, Reference};
+use serde_json::Value;
+
+use super::McpClient;
+use super::util::{json_value, map_service_error};
+
+impl McpClient {
+    /// Asks the server for values of a prompt argument or resource template variable that
+    /// start with `value`.
+    pub async fn complete(
+        &self,
+        reference: Reference,
+        argument: String,
+        value: String,
+    ) -> Result<Value, AppError> {
+        self.retry("complete", || {
+            let params = CompleteRequestParam {
+                r#ref: reference.clone(),
+                argument: ArgumentInfo {
+                    name: argument.clone(),
+                    value: value.clone(),
+                },
+                context: None,
+            };
+            async move {
+                let service = self.connect(false).await?;
+                let result = service
+                    .peer()
+                    .complete(params)
+                    .await
+                    .map_err(map_service_error);
+                let _ = service.cancel().await;
+                json_value(result?.completion)
+            }
+        })
+        .await
+    }
+}
@@ -1,10 +1,12 @@
-use crate::config::ResolvedConnection;
+use crate::config::{ResolvedConnection, StdioCommand};
 use crate::errors::AppError;
 use crate::types::Header;
 use crate::user_agent::UserAgentPreset;
 use backoff::ExponentialBackoff;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
 use rmcp::model::ClientInfo;
+use rmcp::transport::TokioChildProcess;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 pub(crate) fn build_backoff(base_delay_ms: Option<u64>) -> ExponentialBackoff {
@@ -43,6 +45,30 @@ pub(crate) fn build_http_client(
         .map_err(|err| AppError::new("http_client", format!("Failed to build client: {err}")))
 }
 
+/// Spawns the stdio server, with `env` replacing the inherited environment when given.
+pub(crate) fn build_child_process(
+    command: &StdioCommand,
+    env: Option<&BTreeMap<String, String>>,
+) -> Result<TokioChildProcess, AppError> {
+    let mut cmd = tokio::process::Command::new(&command.command);
+    if let Some(env) = env {
+        cmd.env_clear().envs(env);
+    }
+    cmd.args(&command.args)
+        .envs(&command.env)
+        .kill_on_drop(true);
+    if let Some(cwd) = &command.cwd {
+        cmd.current_dir(cwd);
+    }
+
+    TokioChildProcess::new(cmd).map_err(|err| {
+        AppError::new(
+            "process_spawn",
+            format!("Failed to start {}: {err}", command.command),
+        )
+    })
+}
+
 pub(crate) fn split_headers(headers: &[Header]) -> Result<(HeaderMap, Option<String>), AppError> {
     let mut header_map = HeaderMap::new();
     let mut auth_token = None;
@@ -1,14 +1,53 @@
-use crate::config::ResolvedConnection;
+use crate::config::{ResolvedConnection, TransportTarget};
 use crate::errors::AppError;
-use rmcp::model::ServerInfo;
-use rmcp::service::ServiceExt;
+use rmcp::model::{ServerInfo, SetLevelRequestParam, Tool};
+use rmcp::service::{ClientInitializeError, RoleClient, RunningService, ServiceExt};
+use rmcp::transport::StreamableHttpClientTransport;
+use rmcp::transport::streamable_http_client::{
+    StreamableHttpClientTransportConfig, StreamableHttpError,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
-use super::connection::{build_backoff, build_client_info, build_http_client, split_headers};
-use super::notify::ClientHandlerImpl;
-use super::util::map_init_error;
+use super::connection::{
+    build_backoff, build_child_process, build_client_info, build_http_client, split_headers,
+};
+use super::list_tools::find_tool;
+use super::notify::{ClientHandlerImpl, NotificationSink};
+use super::sse::SseClientTransport;
+use super::tool_cache::ToolCache;
+use super::util::{map_init_error, map_service_error};
+
+pub(crate) type Service = RunningService<RoleClient, ClientHandlerImpl>;
 
 pub struct McpClient {
     connection: ResolvedConnection,
+    /// Access token refreshed during this run, replacing the one from the token store.
+    refreshed_token: Mutex<Option<String>>,
+    /// Receives progress and log notifications from sessions that emit them.
+    notifications: Arc<NotificationSink>,
+    /// Tool definitions fetched from the server during this run, by name.
+    tools: Mutex<HashMap<String, Tool>>,
+    tool_cache: ToolCache,
+}
+
+/// Transport a session was established over, after any fallback.
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum TransportUsed {
+    StreamableHttp,
+    Sse,
+    Stdio,
+}
+
+impl TransportUsed {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            TransportUsed::StreamableHttp => "streamable-http",
+            TransportUsed::Sse => "sse",
+            TransportUsed::Stdio => "stdio",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -16,15 +55,28 @@ pub enum CapabilityKind {
     Tools,
     Resources,
     Prompts,
+    Completions,
 }
 
 impl McpClient {
     pub fn new(connection: ResolvedConnection) -> Self {
-        Self { connection }
+        Self {
+            refreshed_token: Mutex::new(None),
+            notifications: Arc::default(),
+            tools: Mutex::default(),
+            tool_cache: ToolCache::new(&connection),
+            connection,
+        }
+    }
+
+    /// Replaces the default sink, which writes text notifications to stderr.
+    pub fn with_notifications(mut self, notifications: Arc<NotificationSink>) -> Self {
+        self.notifications = notifications;
+        self
     }
 
     pub async fn ensure_capability(&self, capability: CapabilityKind) -> Result<(), AppError> {
-        let info = self.fetch_info().await?;
+        let (info, _) = self.fetch_info().await?;
         if supports_capability(&info, capability) {
             Ok(())
         } else {
@@ -38,29 +90,183 @@ impl McpClient {
         }
     }
 
-    pub(crate) async fn connect(
+    /// Looks up a tool's definition, reusing one fetched earlier in this run or cached by
+    /// an earlier run.
+    pub(crate) async fn tool_definition(
+        &self,
+        service: &Service,
+        name: &str,
+    ) -> Result<Tool, AppError> {
+        if let Some(tool) = self.tools.lock().unwrap().get(name) {
+            return Ok(tool.clone());
+        }
+        match self.tool_cache.get(name) {
+            Some(tool) => Ok(tool),
+            None => self.fetch_tool_definition(service, name).await,
+        }
+    }
+
+    /// Whether the tool's definition was fetched from the server during this run, so it
+    /// cannot be out of date.
+    pub(crate) fn is_tool_definition_fresh(&self, name: &str) -> bool {
+        self.tools.lock().unwrap().contains_key(name)
+    }
+
+    /// Fetches a tool's definition from the server, replacing any cached copy.
+    pub(crate) async fn fetch_tool_definition(
+        &self,
+        service: &Service,
+        name: &str,
+    ) -> Result<Tool, AppError> {
+        let tool = find_tool(service, name)
+            .await?
+            .ok_or_else(|| AppError::new("not_found", format!("Tool '{name}' not found")))?;
+        self.tools
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), tool.clone());
+        self.tool_cache.put(&tool);
+        Ok(tool)
+    }
+
+    pub(crate) async fn connect(&self, emit_notifications: bool) -> Result<Service, AppError> {
+        self.connect_with(self.handler(emit_notifications)).await
+    }
+
+    /// Opens a session and applies the configured log level when the server supports logging.
+    pub(crate) async fn connect_with(
+        &self,
+        handler: ClientHandlerImpl,
+    ) -> Result<Service, AppError> {
+        let (service, _) = self.open_session_with(handler).await?;
+        if let Some(level) = self.connection.log_level
+            && service
+                .peer_info()
+                .is_some_and(|info| info.capabilities.logging.is_some())
+        {
+            service
+                .set_level(SetLevelRequestParam {
+                    level: level.into(),
+                })
+                .await
+                .map_err(map_service_error)?;
+        }
+        Ok(service)
+    }
+
+    /// Connects and initializes a session, reporting which transport ended up being used.
+    ///
+    /// A 401 with a refreshable token (see `ResolvedConnection::refreshable_token`) triggers
+    /// one refresh and retry.
+    pub(crate) async fn open_session(
         &self,
         emit_notifications: bool,
-    ) -> Result<rmcp::service::RunningService<rmcp::service::RoleClient, ClientHandlerImpl>, AppError>
-    {
-        let (headers, auth_token) = split_headers(&self.connection.headers)?;
+    ) -> Result<(Service, TransportUsed), AppError> {
+        self.open_session_with(self.handler(emit_notifications))
+            .await
+    }
+
+    pub(crate) async fn open_session_with(
+        &self,
+        handler: ClientHandlerImpl,
+    ) -> Result<(Service, TransportUsed), AppError> {
+        match self.open_session_once(handler.clone()).await {
+            Err(err) if err.code() == "auth_required" && self.connection.refreshable_token => {
+                let token = crate::auth::refresh_access_token(&self.connection).await?;
+                *self.refreshed_token.lock().unwrap() = Some(token);
+                self.open_session_once(handler).await
+            }
+            result => result,
+        }
+    }
+
+    /// Client handler that forwards notifications to the sink when `emit_notifications` is set.
+    pub(crate) fn handler(&self, emit_notifications: bool) -> ClientHandlerImpl {
+        ClientHandlerImpl::new(
+            build_client_info(&self.connection),
+            emit_notifications.then(|| self.notifications.clone()),
+        )
+    }
+
+    async fn open_session_once(
+        &self,
+        handler: ClientHandlerImpl,
+    ) -> Result<(Service, TransportUsed), AppError> {
+        if self.connection.use_daemon
+            && let Some(session) = crate::daemon::open_session(&self.connection, &handler).await?
+        {
+            return Ok(session);
+        }
+        match &self.connection.transport {
+            TransportTarget::Http { url } => {
+                let (client, auth_token) = self.http_client()?;
+                let mut config = StreamableHttpClientTransportConfig::with_uri(url.clone());
+                if let Some(token) = auth_token.clone() {
+                    config = config.auth_header(token);
+                }
+                let transport = StreamableHttpClientTransport::with_client(client.clone(), config);
+                match handler.clone().serve(transport).await {
+                    Ok(service) => Ok((service, TransportUsed::StreamableHttp)),
+                    // Servers that only speak the legacy transport reject the initialize POST
+                    // with a 4xx; retry the handshake over HTTP+SSE on the same URL.
+                    Err(err) if rejects_streamable_http(&err) => {
+                        let transport = SseClientTransport::connect(
+                            client,
+                            self.stream_http_client()?,
+                            url,
+                            auth_token,
+                        )
+                        .await?;
+                        let service = handler.serve(transport).await.map_err(map_init_error)?;
+                        Ok((service, TransportUsed::Sse))
+                    }
+                    Err(err) if is_unauthorized(&err) => Err(unauthorized_error(url)),
+                    Err(err) => Err(map_init_error(err)),
+                }
+            }
+            TransportTarget::Sse { url } => {
+                let (client, auth_token) = self.http_client()?;
+                let transport = SseClientTransport::connect(
+                    client,
+                    self.stream_http_client()?,
+                    url,
+                    auth_token,
+                )
+                .await?;
+                let service = handler.serve(transport).await.map_err(map_init_error)?;
+                Ok((service, TransportUsed::Sse))
+            }
+            TransportTarget::Stdio(command) => {
+                let transport = build_child_process(command, self.connection.env.as_ref())?;
+                let service = handler.serve(transport).await.map_err(map_init_error)?;
+                Ok((service, TransportUsed::Stdio))
+            }
+        }
+    }
+
+    fn http_client(&self) -> Result<(reqwest::Client, Option<String>), AppError> {
+        let (headers, mut auth_token) = split_headers(&self.connection.headers)?;
+        if let Some(token) = self.refreshed_token.lock().unwrap().clone() {
+            auth_token = Some(token);
+        }
         let client = build_http_client(
             &headers,
             self.connection.timeout,
             self.connection.connect_timeout,
             &self.connection.user_agent,
         )?;
-        let mut config =
-            rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig::with_uri(
-                self.connection.url.clone(),
-            );
-        if let Some(token) = auth_token {
-            config = config.auth_header(token);
-        }
-        let transport = rmcp::transport::StreamableHttpClientTransport::with_client(client, config);
-        let handler =
-            ClientHandlerImpl::new(build_client_info(&self.connection), emit_notifications);
-        handler.serve(transport).await.map_err(map_init_error)
+        Ok((client, auth_token))
+    }
+
+    /// Client for long-lived event streams: like `http_client`, without the request timeout.
+    fn stream_http_client(&self) -> Result<reqwest::Client, AppError> {
+        let (headers, _) = split_headers(&self.connection.headers)?;
+        build_http_client(
+            &headers,
+            None,
+            self.connection.connect_timeout,
+            &self.connection.user_agent,
+        )
     }
 
     pub(crate) async fn retry<T, F, Fut>(
@@ -84,7 +290,14 @@ impl McpClient {
                 match fut.await {
                     Ok(result) => Ok(result),
                     Err(err) => {
-                        if attempt >= max_attempts {
+                        // Retrying with the same rejected credentials or params, or for
+                        // something the server does not have, cannot succeed.
+                        if attempt >= max_attempts
+                            || matches!(
+                                err.code(),
+                                "auth_required" | "invalid_params" | "not_found"
+                            )
+                        {
                             Err(backoff::Error::permanent(err))
                         } else {
                             Err(backoff::Error::transient(err))
@@ -96,25 +309,63 @@ impl McpClient {
         .await
     }
 
-    pub(crate) async fn fetch_info(&self) -> Result<ServerInfo, AppError> {
+    pub(crate) async fn fetch_info(&self) -> Result<(ServerInfo, TransportUsed), AppError> {
         self.retry("get-info", || async {
-            let service = self.connect(false).await?;
+            let (service, transport) = self.open_session(false).await?;
             let info = service
                 .peer_info()
                 .cloned()
                 .ok_or_else(|| AppError::new("missing_server_info", "Server info not available"))?;
             let _ = service.cancel().await;
-            Ok(info)
+            Ok((info, transport))
         })
         .await
     }
 }
 
+fn streamable_http_error(
+    err: &ClientInitializeError,
+) -> Option<&StreamableHttpError<reqwest::Error>> {
+    let ClientInitializeError::TransportError { error, .. } = err else {
+        return None;
+    };
+    error
+        .error
+        .downcast_ref::<StreamableHttpError<reqwest::Error>>()
+}
+
+fn rejects_streamable_http(err: &ClientInitializeError) -> bool {
+    let Some(StreamableHttpError::Client(err)) = streamable_http_error(err) else {
+        return false;
+    };
+    err.status().is_some_and(|status| {
+        status.is_client_error() && status != reqwest::StatusCode::UNAUTHORIZED
+    })
+}
+
+fn is_unauthorized(err: &ClientInitializeError) -> bool {
+    match streamable_http_error(err) {
+        Some(StreamableHttpError::AuthRequired(_)) => true,
+        Some(StreamableHttpError::Client(err)) => {
+            err.status() == Some(reqwest::StatusCode::UNAUTHORIZED)
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn unauthorized_error(url: &str) -> AppError {
+    AppError::new(
+        "auth_required",
+        format!("Server rejected credentials (401): {url}"),
+    )
+}
+
 fn supports_capability(info: &ServerInfo, capability: CapabilityKind) -> bool {
     match capability {
         CapabilityKind::Tools => info.capabilities.tools.is_some(),
         CapabilityKind::Resources => info.capabilities.resources.is_some(),
         CapabilityKind::Prompts => info.capabilities.prompts.is_some(),
+        CapabilityKind::Completions => info.capabilities.completions.is_some(),
     }
 }
 
@@ -123,5 +374,6 @@ fn capability_name(capability: CapabilityKind) -> &'static str {
         CapabilityKind::Tools => "tools",
         CapabilityKind::Resources => "resources",
         CapabilityKind::Prompts => "prompts",
+        CapabilityKind::Completions => "completions",
     }
 }
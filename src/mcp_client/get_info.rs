@@ -6,7 +6,14 @@ use super::util::json_value;
 
 impl McpClient {
     pub async fn get_info(&self) -> Result<Value, AppError> {
-        let info = self.fetch_info().await?;
-        json_value(info)
+        let (info, transport) = self.fetch_info().await?;
+        let mut value = json_value(info)?;
+        if let Value::Object(map) = &mut value {
+            map.insert(
+                "transport".to_string(),
+                Value::String(transport.as_str().to_string()),
+            );
+        }
+        Ok(value)
     }
 }
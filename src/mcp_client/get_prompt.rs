@@ -1,9 +1,8 @@
 use crate::errors::AppError;
-use rmcp::model::GetPromptRequestParam;
+use rmcp::model::{GetPromptRequestParam, GetPromptResult};
 use serde_json::Value;
 
 use super::McpClient;
-use super::text::prompt_messages_to_text;
 use super::util::{map_service_error, value_to_object};
 
 impl McpClient {
@@ -11,7 +10,7 @@ impl McpClient {
         &self,
         prompt_id: String,
         params: Option<Value>,
-    ) -> Result<String, AppError> {
+    ) -> Result<GetPromptResult, AppError> {
         self.retry("get-prompt", || {
             let prompt_id = prompt_id.clone();
             let params = params.clone();
@@ -27,8 +26,7 @@ impl McpClient {
                     .await
                     .map_err(map_service_error)?;
                 let _ = service.cancel().await;
-                let text = prompt_messages_to_text(&result.messages);
-                Ok(text)
+                Ok(result)
             }
         })
         .await
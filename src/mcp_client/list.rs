@@ -0,0 +1,56 @@
+use crate::errors::AppError;
+use serde_json::Value;
+
+use super::McpClient;
+use super::core::CapabilityKind;
+use super::paginate::Pagination;
+use super::search::NameFilter;
+
+/// Item type of a list command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListKind {
+    Tools,
+    Resources,
+    ResourceTemplates,
+    Prompts,
+}
+
+impl ListKind {
+    pub fn capability(self) -> CapabilityKind {
+        match self {
+            ListKind::Tools => CapabilityKind::Tools,
+            ListKind::Resources | ListKind::ResourceTemplates => CapabilityKind::Resources,
+            ListKind::Prompts => CapabilityKind::Prompts,
+        }
+    }
+
+    /// Key of the item array in the list output.
+    pub fn items_key(self, short: bool) -> &'static str {
+        match self {
+            ListKind::Tools => "tools",
+            ListKind::Resources => "resources",
+            ListKind::ResourceTemplates if short => "resource_templates",
+            ListKind::ResourceTemplates => "resourceTemplates",
+            ListKind::Prompts => "prompts",
+        }
+    }
+}
+
+impl McpClient {
+    pub async fn list(
+        &self,
+        kind: ListKind,
+        pagination: &Pagination,
+        name: Option<&NameFilter>,
+        short: bool,
+    ) -> Result<Value, AppError> {
+        match kind {
+            ListKind::Tools => self.list_tools(pagination, name, short).await,
+            ListKind::Resources => self.list_resources(pagination, name, short).await,
+            ListKind::ResourceTemplates => {
+                self.list_resource_templates(pagination, name, short).await
+            }
+            ListKind::Prompts => self.list_prompts(pagination, name, short).await,
+        }
+    }
+}
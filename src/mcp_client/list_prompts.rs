@@ -3,6 +3,9 @@ use rmcp::model::{ListPromptsResult, PaginatedRequestParam, Prompt};
 use serde_json::Value;
 
 use super::McpClient;
+use super::core::Service;
+use super::paginate::{Pagination, mark_truncated};
+use super::search::NameFilter;
 use super::util::{json_value, map_service_error};
 
 #[derive(serde::Serialize)]
@@ -27,76 +30,59 @@ struct ShortPromptsResult {
     next_cursor: Option<String>,
 }
 
+async fn fetch_prompts(
+    service: &Service,
+    params: Option<PaginatedRequestParam>,
+) -> Result<(Vec<Prompt>, Option<String>), AppError> {
+    let result = service
+        .peer()
+        .list_prompts(params)
+        .await
+        .map_err(map_service_error)?;
+    Ok((result.prompts, result.next_cursor))
+}
+
 impl McpClient {
     pub async fn list_prompts(
         &self,
-        cursor: Option<String>,
-        name: Option<&str>,
+        pagination: &Pagination,
+        name: Option<&NameFilter>,
         short: bool,
     ) -> Result<Value, AppError> {
         self.retry("list-prompts", || async {
             let service = self.connect(false).await?;
-            let output = match name {
-                Some(name) => {
-                    let mut next = cursor.clone();
-                    let mut matched = Vec::new();
-
-                    loop {
-                        let params = next.clone().map(|cursor| PaginatedRequestParam {
-                            cursor: Some(cursor),
-                        });
-                        let result = service
-                            .peer()
-                            .list_prompts(params)
-                            .await
-                            .map_err(map_service_error)?;
-                        matched.extend(
-                            result
-                                .prompts
-                                .into_iter()
-                                .filter(|prompt| prompt.name == name),
-                        );
-                        match result.next_cursor {
-                            Some(cursor) => next = Some(cursor),
-                            None => break,
-                        }
-                    }
+            let page = pagination
+                .collect(
+                    name.is_some(),
+                    |prompt: &Prompt| name.is_none_or(|name| name.matches(&prompt.name)),
+                    |params| fetch_prompts(&service, params),
+                )
+                .await;
+            let _ = service.cancel().await;
+            let page = page?;
 
-                    if matched.is_empty() {
-                        Err(AppError::new(
-                            "not_found",
-                            format!("Prompt '{}' not found", name),
-                        ))
-                    } else if short {
-                        json_value(ShortPromptsResult {
-                            prompts: matched.into_iter().map(ShortPrompt::from).collect(),
-                            next_cursor: None,
-                        })
-                    } else {
-                        json_value(ListPromptsResult::with_all_items(matched))
-                    }
-                }
-                None => {
-                    let params = cursor.clone().map(|cursor| PaginatedRequestParam {
-                        cursor: Some(cursor),
-                    });
-                    let result = service
-                        .peer()
-                        .list_prompts(params)
-                        .await
-                        .map_err(map_service_error)?;
-                    if short {
-                        json_value(ShortPromptsResult {
-                            prompts: result.prompts.into_iter().map(ShortPrompt::from).collect(),
-                            next_cursor: result.next_cursor,
-                        })
-                    } else {
-                        json_value(result)
-                    }
-                }
+            if let Some(name) = name
+                && page.items.is_empty()
+            {
+                return Err(AppError::new(
+                    "not_found",
+                    format!("Prompt '{}' not found", name.pattern()),
+                ));
+            }
+            let next_offset = page.next_offset;
+            let result = if short {
+                json_value(ShortPromptsResult {
+                    prompts: page.items.into_iter().map(ShortPrompt::from).collect(),
+                    next_cursor: page.next_cursor,
+                })
+            } else {
+                json_value(ListPromptsResult {
+                    prompts: page.items,
+                    next_cursor: page.next_cursor,
+                    ..ListPromptsResult::default()
+                })
             };
-            let _ = service.cancel().await;
-            output
+            Ok(mark_truncated(result?, next_offset))
         })
         .await
     }
@@ -0,0 +1,97 @@
+use crate::errors::AppError;
+use rmcp::model::{ListResourceTemplatesResult, PaginatedRequestParam, ResourceTemplate};
+use serde_json::Value;
+
+use super::McpClient;
+use super::core::Service;
+use super::paginate::{Pagination, mark_truncated};
+use super::search::NameFilter;
+use super::util::{json_value, map_service_error};
+
+#[derive(serde::Serialize)]
+struct ShortResourceTemplate {
+    name: String,
+    uri_template: String,
+    description: Option<String>,
+}
+
+impl From<ResourceTemplate> for ShortResourceTemplate {
+    fn from(template: ResourceTemplate) -> Self {
+        ShortResourceTemplate {
+            name: template.name.clone(),
+            uri_template: template.uri_template.clone(),
+            description: template.description.clone(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ShortResourceTemplatesResult {
+    resource_templates: Vec<ShortResourceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+async fn fetch_resource_templates(
+    service: &Service,
+    params: Option<PaginatedRequestParam>,
+) -> Result<(Vec<ResourceTemplate>, Option<String>), AppError> {
+    let result = service
+        .peer()
+        .list_resource_templates(params)
+        .await
+        .map_err(map_service_error)?;
+    Ok((result.resource_templates, result.next_cursor))
+}
+
+impl McpClient {
+    pub async fn list_resource_templates(
+        &self,
+        pagination: &Pagination,
+        name: Option<&NameFilter>,
+        short: bool,
+    ) -> Result<Value, AppError> {
+        self.retry("list-resource-templates", || async {
+            let service = self.connect(false).await?;
+            let page = pagination
+                .collect(
+                    name.is_some(),
+                    |template: &ResourceTemplate| {
+                        name.is_none_or(|name| name.matches(&template.name))
+                    },
+                    |params| fetch_resource_templates(&service, params),
+                )
+                .await;
+            let _ = service.cancel().await;
+            let page = page?;
+
+            if let Some(name) = name
+                && page.items.is_empty()
+            {
+                return Err(AppError::new(
+                    "not_found",
+                    format!("Resource template '{}' not found", name.pattern()),
+                ));
+            }
+            let next_offset = page.next_offset;
+            let result = if short {
+                json_value(ShortResourceTemplatesResult {
+                    resource_templates: page
+                        .items
+                        .into_iter()
+                        .map(ShortResourceTemplate::from)
+                        .collect(),
+                    next_cursor: page.next_cursor,
+                })
+            } else {
+                json_value(ListResourceTemplatesResult {
+                    resource_templates: page.items,
+                    next_cursor: page.next_cursor,
+                    ..ListResourceTemplatesResult::default()
+                })
+            };
+            Ok(mark_truncated(result?, next_offset))
+        })
+        .await
+    }
+}
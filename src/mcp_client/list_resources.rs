@@ -3,6 +3,9 @@ use rmcp::model::{ListResourcesResult, PaginatedRequestParam, Resource};
 use serde_json::Value;
 
 use super::McpClient;
+use super::core::Service;
+use super::paginate::{Pagination, mark_truncated};
+use super::search::NameFilter;
 use super::util::{json_value, map_service_error};
 
 #[derive(serde::Serialize)]
@@ -29,80 +32,59 @@ struct ShortResourcesResult {
     next_cursor: Option<String>,
 }
 
+async fn fetch_resources(
+    service: &Service,
+    params: Option<PaginatedRequestParam>,
+) -> Result<(Vec<Resource>, Option<String>), AppError> {
+    let result = service
+        .peer()
+        .list_resources(params)
+        .await
+        .map_err(map_service_error)?;
+    Ok((result.resources, result.next_cursor))
+}
+
 impl McpClient {
     pub async fn list_resources(
         &self,
-        cursor: Option<String>,
-        name: Option<&str>,
+        pagination: &Pagination,
+        name: Option<&NameFilter>,
         short: bool,
     ) -> Result<Value, AppError> {
         self.retry("list-resources", || async {
             let service = self.connect(false).await?;
-            let output = match name {
-                Some(name) => {
-                    let mut next = cursor.clone();
-                    let mut matched = Vec::new();
-
-                    loop {
-                        let params = next.clone().map(|cursor| PaginatedRequestParam {
-                            cursor: Some(cursor),
-                        });
-                        let result = service
-                            .peer()
-                            .list_resources(params)
-                            .await
-                            .map_err(map_service_error)?;
-                        matched.extend(
-                            result
-                                .resources
-                                .into_iter()
-                                .filter(|resource| resource.name == name),
-                        );
-                        match result.next_cursor {
-                            Some(cursor) => next = Some(cursor),
-                            None => break,
-                        }
-                    }
+            let page = pagination
+                .collect(
+                    name.is_some(),
+                    |resource: &Resource| name.is_none_or(|name| name.matches(&resource.name)),
+                    |params| fetch_resources(&service, params),
+                )
+                .await;
+            let _ = service.cancel().await;
+            let page = page?;
 
-                    if matched.is_empty() {
-                        Err(AppError::new(
-                            "not_found",
-                            format!("Resource '{}' not found", name),
-                        ))
-                    } else if short {
-                        json_value(ShortResourcesResult {
-                            resources: matched.into_iter().map(ShortResource::from).collect(),
-                            next_cursor: None,
-                        })
-                    } else {
-                        json_value(ListResourcesResult::with_all_items(matched))
-                    }
-                }
-                None => {
-                    let params = cursor.clone().map(|cursor| PaginatedRequestParam {
-                        cursor: Some(cursor),
-                    });
-                    let result = service
-                        .peer()
-                        .list_resources(params)
-                        .await
-                        .map_err(map_service_error)?;
-                    if short {
-                        json_value(ShortResourcesResult {
-                            resources: result
-                                .resources
-                                .into_iter()
-                                .map(ShortResource::from)
-                                .collect(),
-                            next_cursor: result.next_cursor,
-                        })
-                    } else {
-                        json_value(result)
-                    }
-                }
+            if let Some(name) = name
+                && page.items.is_empty()
+            {
+                return Err(AppError::new(
+                    "not_found",
+                    format!("Resource '{}' not found", name.pattern()),
+                ));
+            }
+            let next_offset = page.next_offset;
+            let result = if short {
+                json_value(ShortResourcesResult {
+                    resources: page.items.into_iter().map(ShortResource::from).collect(),
+                    next_cursor: page.next_cursor,
+                })
+            } else {
+                json_value(ListResourcesResult {
+                    resources: page.items,
+                    next_cursor: page.next_cursor,
+                    ..ListResourcesResult::default()
+                })
             };
-            let _ = service.cancel().await;
-            output
+            Ok(mark_truncated(result?, next_offset))
         })
         .await
     }
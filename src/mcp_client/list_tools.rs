@@ -3,12 +3,18 @@ use rmcp::model::{ListToolsResult, PaginatedRequestParam, Tool};
 use serde_json::Value;
 
 use super::McpClient;
+use super::core::Service;
+use super::paginate::{Pagination, mark_truncated};
+use super::search::NameFilter;
 use super::util::{json_value, map_service_error};
 
 #[derive(serde::Serialize)]
 struct ShortTool {
     name: String,
     description: Option<String>,
+    /// Only filled for exact `--name` lookups, where the caller wants to know the result shape.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_schema: Option<Value>,
 }
 
 impl From<Tool> for ShortTool {
@@ -16,6 +22,20 @@ impl From<Tool> for ShortTool {
         ShortTool {
             name: tool.name.to_string(),
             description: tool.description.map(|s| s.to_string()),
+            output_schema: None,
+        }
+    }
+}
+
+impl ShortTool {
+    fn with_output_schema(tool: Tool) -> Self {
+        let output_schema = tool
+            .output_schema
+            .as_ref()
+            .map(|schema| Value::Object(schema.as_ref().clone()));
+        ShortTool {
+            output_schema,
+            ..ShortTool::from(tool)
         }
     }
 }
@@ -27,76 +47,79 @@ struct ShortToolsResult {
     next_cursor: Option<String>,
 }
 
+/// Looks up a tool by name, following pagination.
+pub(crate) async fn find_tool(service: &Service, name: &str) -> Result<Option<Tool>, AppError> {
+    let lookup = Pagination {
+        limit: Some(1),
+        ..Pagination::default()
+    };
+    let page = lookup
+        .collect(
+            true,
+            |tool: &Tool| tool.name == name,
+            |params| fetch_tools(service, params),
+        )
+        .await?;
+    Ok(page.items.into_iter().next())
+}
+
+pub(crate) async fn fetch_tools(
+    service: &Service,
+    params: Option<PaginatedRequestParam>,
+) -> Result<(Vec<Tool>, Option<String>), AppError> {
+    let result = service
+        .peer()
+        .list_tools(params)
+        .await
+        .map_err(map_service_error)?;
+    Ok((result.tools, result.next_cursor))
+}
+
 impl McpClient {
     pub async fn list_tools(
         &self,
-        cursor: Option<String>,
-        name: Option<&str>,
+        pagination: &Pagination,
+        name: Option<&NameFilter>,
         short: bool,
     ) -> Result<Value, AppError> {
         self.retry("list-tools", || async {
             let service = self.connect(false).await?;
-            let output = match name {
-                Some(name) => {
-                    let mut next = cursor.clone();
-                    let mut matched = Vec::new();
-
-                    loop {
-                        let params = next.clone().map(|cursor| PaginatedRequestParam {
-                            cursor: Some(cursor),
-                        });
-                        let result = service
-                            .peer()
-                            .list_tools(params)
-                            .await
-                            .map_err(map_service_error)?;
-                        matched.extend(
-                            result
-                                .tools
-                                .into_iter()
-                                .filter(|tool| tool.name.as_ref() == name),
-                        );
-                        match result.next_cursor {
-                            Some(cursor) => next = Some(cursor),
-                            None => break,
-                        }
-                    }
+            let page = pagination
+                .collect(
+                    name.is_some(),
+                    |tool: &Tool| name.is_none_or(|name| name.matches(&tool.name)),
+                    |params| fetch_tools(&service, params),
+                )
+                .await;
+            let _ = service.cancel().await;
+            let page = page?;
 
-                    if matched.is_empty() {
-                        Err(AppError::new(
-                            "not_found",
-                            format!("Tool '{}' not found", name),
-                        ))
-                    } else if short {
-                        json_value(ShortToolsResult {
-                            tools: matched.into_iter().map(ShortTool::from).collect(),
-                            next_cursor: None,
-                        })
-                    } else {
-                        json_value(ListToolsResult::with_all_items(matched))
-                    }
-                }
-                None => {
-                    let params = cursor.clone().map(|cursor| PaginatedRequestParam {
-                        cursor: Some(cursor),
-                    });
-                    let result = service
-                        .peer()
-                        .list_tools(params)
-                        .await
-                        .map_err(map_service_error)?;
-                    if short {
-                        json_value(ShortToolsResult {
-                            tools: result.tools.into_iter().map(ShortTool::from).collect(),
-                            next_cursor: result.next_cursor,
-                        })
-                    } else {
-                        json_value(result)
-                    }
-                }
+            if let Some(name) = name
+                && page.items.is_empty()
+            {
+                return Err(AppError::new(
+                    "not_found",
+                    format!("Tool '{}' not found", name.pattern()),
+                ));
+            }
+            let next_offset = page.next_offset;
+            let result = if short {
+                let short_tool = match name {
+                    Some(name) if name.is_exact() => ShortTool::with_output_schema,
+                    _ => ShortTool::from,
+                };
+                json_value(ShortToolsResult {
+                    tools: page.items.into_iter().map(short_tool).collect(),
+                    next_cursor: page.next_cursor,
+                })
+            } else {
+                json_value(ListToolsResult {
+                    tools: page.items,
+                    next_cursor: page.next_cursor,
+                    ..ListToolsResult::default()
+                })
             };
-            let _ = service.cancel().await;
-            output
+            Ok(mark_truncated(result?, next_offset))
         })
         .await
     }
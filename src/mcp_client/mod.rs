@@ -1,14 +1,40 @@
+mod arguments;
 mod call_tool;
+mod complete;
 mod connection;
 mod core;
 mod get_info;
 mod get_prompt;
+mod list;
 mod list_prompts;
+mod list_resource_templates;
 mod list_resources;
 mod list_tools;
 mod notify;
+mod output_dir;
+mod paginate;
 mod read_resource;
+mod schema;
+mod search;
+mod sse;
 mod text;
+mod tool_cache;
+mod uri_template;
 mod util;
+mod watch_resource;
 
+pub use arguments::{ArgPair, Arguments};
+pub(crate) use connection::build_http_client;
 pub use core::{CapabilityKind, McpClient};
+pub(crate) use core::{Service, TransportUsed};
+pub use list::ListKind;
+pub(crate) use notify::{ClientHandlerImpl, Relay, Relayed};
+pub use notify::{NotificationFormat, NotificationSink, NotificationTarget};
+pub use output_dir::OutputDir;
+pub use paginate::Pagination;
+pub use read_resource::expand_resource_template;
+pub use search::{MatchMode, NameFilter};
+pub(crate) use text::{
+    call_tool_result_to_text, prompt_messages_to_text, read_resource_result_to_text,
+};
+pub(crate) use util::map_init_error;
@@ -1,22 +1,269 @@
+use crate::errors::AppError;
 use rmcp::{
     handler::client::ClientHandler,
-    model::{ClientInfo, LoggingMessageNotificationParam, ProgressNotificationParam},
+    model::{
+        ClientInfo, LoggingMessageNotification, LoggingMessageNotificationParam,
+        ProgressNotification, ProgressNotificationParam, ResourceUpdatedNotification,
+        ResourceUpdatedNotificationParam, ServerNotification,
+    },
     service::{NotificationContext, RoleClient},
 };
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+
+/// Where progress and log notifications are written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NotificationTarget {
+    None,
+    #[default]
+    Stderr,
+    Stdout,
+    /// Appended to this file.
+    File(PathBuf),
+}
+
+impl FromStr for NotificationTarget {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(NotificationTarget::None),
+            "stderr" => Ok(NotificationTarget::Stderr),
+            "stdout" => Ok(NotificationTarget::Stdout),
+            _ => match value.strip_prefix("file:") {
+                Some(path) if !path.is_empty() => Ok(NotificationTarget::File(path.into())),
+                _ => Err(format!(
+                    "expected none, stderr, stdout or file:<path>, got `{value}`"
+                )),
+            },
+        }
+    }
+}
+
+/// How each notification is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum NotificationFormat {
+    /// `[progress] 1/3 message` and `[log:level] logger data` lines
+    #[default]
+    Text,
+    /// One JSON object per line with a timestamp and the progress token
+    Ndjson,
+}
+
+/// Destination shared by every session of a command.
+#[derive(Default)]
+pub struct NotificationSink {
+    target: NotificationTarget,
+    format: NotificationFormat,
+    file: Option<Mutex<File>>,
+}
+
+impl NotificationSink {
+    /// Opens the target file (created or appended to) up front so a bad path fails early.
+    pub fn new(target: NotificationTarget, format: NotificationFormat) -> Result<Self, AppError> {
+        let file = match &target {
+            NotificationTarget::File(path) => {
+                let file = File::options()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|err| {
+                        AppError::new(
+                            "notification_file",
+                            format!(
+                                "Failed to open notification file: {} ({err})",
+                                path.display()
+                            ),
+                        )
+                    })?;
+                Some(Mutex::new(file))
+            }
+            _ => None,
+        };
+        Ok(Self {
+            target,
+            format,
+            file,
+        })
+    }
+
+    fn progress(&self, params: ProgressNotificationParam) {
+        let line = match self.format {
+            NotificationFormat::Text => progress_text(&params),
+            NotificationFormat::Ndjson => json!({
+                "timestamp": timestamp(SystemTime::now()),
+                "type": "progress",
+                "progressToken": params.progress_token,
+                "progress": params.progress,
+                "total": params.total,
+                "message": params.message,
+            })
+            .to_string(),
+        };
+        self.write(&line);
+    }
+
+    fn log(&self, params: LoggingMessageNotificationParam) {
+        let line = match self.format {
+            NotificationFormat::Text => log_text(&params),
+            NotificationFormat::Ndjson => json!({
+                "timestamp": timestamp(SystemTime::now()),
+                "type": "log",
+                "level": params.level,
+                "logger": params.logger,
+                "data": params.data,
+            })
+            .to_string(),
+        };
+        self.write(&line);
+    }
+
+    fn write(&self, line: &str) {
+        match &self.target {
+            NotificationTarget::None => {}
+            NotificationTarget::Stderr => eprintln!("{line}"),
+            NotificationTarget::Stdout => println!("{line}"),
+            NotificationTarget::File(_) => {
+                if let Some(file) = &self.file {
+                    let mut file = file.lock().unwrap();
+                    let _ = writeln!(file, "{line}");
+                }
+            }
+        }
+    }
+}
+
+fn progress_text(params: &ProgressNotificationParam) -> String {
+    let mut line = format!("[progress] {}", params.progress);
+    if let Some(total) = params.total {
+        line.push_str(&format!("/{}", total));
+    }
+    if let Some(message) = &params.message
+        && !message.is_empty()
+    {
+        line.push(' ');
+        line.push_str(message);
+    }
+    line
+}
+
+fn log_text(params: &LoggingMessageNotificationParam) -> String {
+    let level = format!("{:?}", params.level).to_ascii_lowercase();
+    let mut line = format!("[log:{level}]");
+    if let Some(logger) = &params.logger
+        && !logger.is_empty()
+    {
+        line.push(' ');
+        line.push_str(logger);
+    }
+    let data = params.data.to_string();
+    if !data.is_empty() {
+        line.push(' ');
+        line.push_str(&data);
+    }
+    line
+}
+
+/// RFC 3339 UTC timestamp with millisecond precision.
+fn timestamp(time: SystemTime) -> String {
+    let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = duration.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+        duration.subsec_millis()
+    )
+}
+
+/// What a `Relay` queues for the client, in the order the server sent it.
+pub(crate) enum Relayed {
+    Notification(ServerNotification),
+    /// Answered once every notification queued before it has been forwarded.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Passes the notifications of a pooled daemon session on to the client currently using it.
+#[derive(Clone, Default)]
+pub(crate) struct Relay(Arc<Mutex<Option<UnboundedSender<Relayed>>>>);
+
+impl Relay {
+    /// Sends notifications to `sender` from now on; `None` drops them.
+    pub(crate) fn attach(&self, sender: Option<UnboundedSender<Relayed>>) {
+        *self.0.lock().unwrap() = sender;
+    }
+
+    /// Waits until the notifications relayed so far have reached the client, so a response
+    /// written afterwards cannot overtake them.
+    pub(crate) async fn flush(&self) {
+        let (sender, flushed) = oneshot::channel();
+        let queued = match &*self.0.lock().unwrap() {
+            Some(relay) => relay.send(Relayed::Flush(sender)).is_ok(),
+            None => false,
+        };
+        if queued {
+            let _ = flushed.await;
+        }
+    }
+
+    fn send(&self, notification: impl Into<ServerNotification>) {
+        if let Some(sender) = &*self.0.lock().unwrap() {
+            let _ = sender.send(Relayed::Notification(notification.into()));
+        }
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct ClientHandlerImpl {
     info: ClientInfo,
-    emit_notifications: bool,
+    notifications: Option<Arc<NotificationSink>>,
+    resource_updates: Option<UnboundedSender<String>>,
+    relay: Option<Relay>,
 }
 
 impl ClientHandlerImpl {
-    pub(crate) fn new(info: ClientInfo, emit_notifications: bool) -> Self {
+    /// `notifications` is `None` for sessions whose notifications are ignored.
+    pub(crate) fn new(info: ClientInfo, notifications: Option<Arc<NotificationSink>>) -> Self {
         Self {
             info,
-            emit_notifications,
+            notifications,
+            resource_updates: None,
+            relay: None,
         }
     }
+
+    /// Sends the URI of every `notifications/resources/updated` to `sender`.
+    pub(crate) fn with_resource_updates(mut self, sender: UnboundedSender<String>) -> Self {
+        self.resource_updates = Some(sender);
+        self
+    }
+
+    /// Forwards progress, log and resource update notifications to `relay` unchanged.
+    pub(crate) fn with_relay(mut self, relay: Relay) -> Self {
+        self.relay = Some(relay);
+        self
+    }
 }
 
 impl ClientHandler for ClientHandlerImpl {
@@ -25,24 +272,12 @@ impl ClientHandler for ClientHandlerImpl {
         params: ProgressNotificationParam,
         _context: NotificationContext<RoleClient>,
     ) -> impl std::future::Future<Output = ()> + Send + '_ {
-        let emit = self.emit_notifications;
-        async move {
-            if !emit {
-                return;
-            }
-
-            let mut line = format!("[progress] {}", params.progress);
-            if let Some(total) = params.total {
-                line.push_str(&format!("/{}", total));
-            }
-            if let Some(message) = params.message
-                && !message.is_empty()
-            {
-                line.push(' ');
-                line.push_str(&message);
-            }
-            println!("{line}");
+        if let Some(relay) = &self.relay {
+            relay.send(ProgressNotification::new(params));
+        } else if let Some(sink) = &self.notifications {
+            sink.progress(params);
         }
+        std::future::ready(())
     }
 
     fn on_logging_message(
@@ -50,30 +285,55 @@ impl ClientHandler for ClientHandlerImpl {
         params: LoggingMessageNotificationParam,
         _context: NotificationContext<RoleClient>,
     ) -> impl std::future::Future<Output = ()> + Send + '_ {
-        let emit = self.emit_notifications;
-        async move {
-            if !emit {
-                return;
-            }
+        if let Some(relay) = &self.relay {
+            relay.send(LoggingMessageNotification::new(params));
+        } else if let Some(sink) = &self.notifications {
+            sink.log(params);
+        }
+        std::future::ready(())
+    }
 
-            let level = format!("{:?}", params.level).to_ascii_lowercase();
-            let mut line = format!("[log:{level}]");
-            if let Some(logger) = params.logger
-                && !logger.is_empty()
-            {
-                line.push(' ');
-                line.push_str(&logger);
-            }
-            let data = params.data.to_string();
-            if !data.is_empty() {
-                line.push(' ');
-                line.push_str(&data);
-            }
-            println!("{line}");
+    fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) -> impl std::future::Future<Output = ()> + Send + '_ {
+        if let Some(relay) = &self.relay {
+            relay.send(ResourceUpdatedNotification::new(params));
+        } else if let Some(sender) = &self.resource_updates {
+            let _ = sender.send(params.uri);
         }
+        std::future::ready(())
     }
 
     fn get_info(&self) -> ClientInfo {
         self.info.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_notification_target() {
+        assert_eq!(
+            "stderr".parse::<NotificationTarget>(),
+            Ok(NotificationTarget::Stderr)
+        );
+        assert_eq!(
+            "file:/tmp/notes.ndjson".parse::<NotificationTarget>(),
+            Ok(NotificationTarget::File("/tmp/notes.ndjson".into()))
+        );
+        assert!("file:".parse::<NotificationTarget>().is_err());
+        assert!("stdin".parse::<NotificationTarget>().is_err());
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(timestamp(time), "2024-02-29T12:34:56.789Z");
+    }
+}
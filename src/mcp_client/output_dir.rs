@@ -0,0 +1,317 @@
+use crate::errors::AppError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::Url;
+use rmcp::model::{
+    CallToolResult, Content, GetPromptResult, Meta, PromptMessageContent, RawContent, RawResource,
+    ReadResourceResult, Resource, ResourceContents,
+};
+use serde_json::{Value, json};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// `_meta` key describing content saved by `OutputDir`: its `path`, `mimeType` and `size`.
+pub(crate) const SAVED_BLOB_META: &str = "call-mcp/savedBlob";
+
+/// Writes base64 images, audio and blobs to files and swaps them for references.
+///
+/// Image and audio content becomes a `file://` resource link. Blob resource contents keep
+/// their resource URI and become `text/uri-list` contents holding the file's URL. Both carry
+/// the file path and the original MIME type under the `SAVED_BLOB_META` key of `_meta`.
+pub struct OutputDir {
+    dir: PathBuf,
+    stem: String,
+    count: usize,
+}
+
+impl OutputDir {
+    /// Creates `dir` if needed; files are named `<stem>-<n>.<ext>`.
+    pub fn new(dir: &Path, stem: &str) -> Result<Self, AppError> {
+        std::fs::create_dir_all(dir).map_err(|err| write_error(dir, &err))?;
+        let dir = dir.canonicalize().map_err(|err| write_error(dir, &err))?;
+        let stem: String = stem
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(Self {
+            dir,
+            stem: if stem.is_empty() {
+                "output".to_string()
+            } else {
+                stem
+            },
+            count: 0,
+        })
+    }
+
+    pub fn save_call_tool(&mut self, result: &mut CallToolResult) -> Result<(), AppError> {
+        result
+            .content
+            .iter_mut()
+            .try_for_each(|content| self.save_content(content))
+    }
+
+    pub fn save_read_resource(&mut self, result: &mut ReadResourceResult) -> Result<(), AppError> {
+        result
+            .contents
+            .iter_mut()
+            .try_for_each(|contents| self.save_resource_contents(contents))
+    }
+
+    pub fn save_prompt(&mut self, result: &mut GetPromptResult) -> Result<(), AppError> {
+        for message in &mut result.messages {
+            match &mut message.content {
+                PromptMessageContent::Image { image } => {
+                    let link = self.save_link(&image.data, &image.mime_type)?;
+                    message.content = PromptMessageContent::ResourceLink { link };
+                }
+                PromptMessageContent::Resource { resource } => {
+                    self.save_resource_contents(&mut resource.raw.resource)?;
+                }
+                PromptMessageContent::Text { .. } | PromptMessageContent::ResourceLink { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn save_content(&mut self, content: &mut Content) -> Result<(), AppError> {
+        let link = match &mut content.raw {
+            RawContent::Image(image) => self.save_link(&image.data, &image.mime_type)?,
+            RawContent::Audio(audio) => self.save_link(&audio.data, &audio.mime_type)?,
+            RawContent::Resource(resource) => {
+                return self.save_resource_contents(&mut resource.resource);
+            }
+            RawContent::Text(_) | RawContent::ResourceLink(_) => return Ok(()),
+        };
+        content.raw = RawContent::ResourceLink(link.raw);
+        Ok(())
+    }
+
+    fn save_resource_contents(&mut self, contents: &mut ResourceContents) -> Result<(), AppError> {
+        let ResourceContents::BlobResourceContents {
+            uri,
+            mime_type,
+            blob,
+            meta,
+        } = contents
+        else {
+            return Ok(());
+        };
+        let mime = mime_type.as_deref().unwrap_or("application/octet-stream");
+        let (path, size) = self.save(blob, mime)?;
+        let mut meta = meta.take().unwrap_or_default();
+        meta.insert(SAVED_BLOB_META.to_string(), saved_meta(&path, mime, size));
+        *contents = ResourceContents::TextResourceContents {
+            uri: std::mem::take(uri),
+            mime_type: Some("text/uri-list".to_string()),
+            text: file_url(&path),
+            meta: Some(meta),
+        };
+        Ok(())
+    }
+
+    fn save_link(&mut self, data: &str, mime_type: &str) -> Result<Resource, AppError> {
+        let (path, size) = self.save(data, mime_type)?;
+        let uri = file_url(&path);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut resource = RawResource::new(uri, name);
+        resource.mime_type = Some(mime_type.to_string());
+        resource.size = u32::try_from(size).ok();
+        let mut meta = Meta::new();
+        meta.insert(
+            SAVED_BLOB_META.to_string(),
+            saved_meta(&path, mime_type, size),
+        );
+        resource.meta = Some(meta);
+        Ok(Resource {
+            raw: resource,
+            annotations: None,
+        })
+    }
+
+    fn save(&mut self, data: &str, mime_type: &str) -> Result<(PathBuf, usize), AppError> {
+        let bytes = STANDARD.decode(data.trim()).map_err(|err| {
+            AppError::new(
+                "invalid_base64",
+                format!("Failed to decode {mime_type} content: {err}"),
+            )
+        })?;
+
+        let extension = extension_for(mime_type);
+        // Files from earlier runs are never overwritten; take the next free number.
+        let (path, mut file) = loop {
+            self.count += 1;
+            let path = self
+                .dir
+                .join(format!("{}-{}.{extension}", self.stem, self.count));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(write_error(&path, &err)),
+            }
+        };
+        file.write_all(&bytes)
+            .map_err(|err| write_error(&path, &err))?;
+        Ok((path, bytes.len()))
+    }
+}
+
+fn saved_meta(path: &Path, mime_type: &str, size: usize) -> Value {
+    json!({ "path": path, "mimeType": mime_type, "size": size })
+}
+
+fn file_url(path: &Path) -> String {
+    Url::from_file_path(path)
+        .map(String::from)
+        .unwrap_or_else(|_| path.display().to_string())
+}
+
+fn write_error(path: &Path, err: &std::io::Error) -> AppError {
+    AppError::new(
+        "output_write",
+        format!("Failed to write output: {} ({err})", path.display()),
+    )
+}
+
+/// File extension for a MIME type, falling back to the subtype or `bin`.
+fn extension_for(mime_type: &str) -> String {
+    let essence = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let known = match essence.as_str() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/svg+xml" => "svg",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/mp4" | "audio/x-m4a" => "m4a",
+        "text/plain" => "txt",
+        "text/markdown" => "md",
+        "application/octet-stream" => "bin",
+        "application/gzip" => "gz",
+        _ => "",
+    };
+    if !known.is_empty() {
+        return known.to_string();
+    }
+
+    let subtype = essence
+        .split_once('/')
+        .map(|(_, subtype)| subtype)
+        .unwrap_or_default();
+    let subtype = subtype.rsplit('+').next().unwrap_or_default();
+    let subtype = subtype.strip_prefix("x-").unwrap_or(subtype);
+    if !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric()) {
+        subtype.to_string()
+    } else {
+        "bin".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_for() {
+        assert_eq!(extension_for("image/png"), "png");
+        assert_eq!(extension_for("image/jpeg"), "jpg");
+        assert_eq!(extension_for("image/svg+xml"), "svg");
+        assert_eq!(extension_for("audio/x-wav"), "wav");
+        assert_eq!(extension_for("application/pdf; charset=binary"), "pdf");
+        assert_eq!(extension_for("application/vnd.foo.bar"), "bin");
+        assert_eq!(extension_for(""), "bin");
+    }
+
+    #[test]
+    fn test_save_read_resource() {
+        let dir = std::env::temp_dir().join(format!("call-mcp-output-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("resource-1.pdf"), "earlier run").unwrap();
+
+        let mut result = ReadResourceResult {
+            contents: vec![
+                ResourceContents::BlobResourceContents {
+                    uri: "file:///report.pdf".to_string(),
+                    mime_type: Some("application/pdf".to_string()),
+                    blob: "aGVsbG8=".to_string(),
+                    meta: None,
+                },
+                ResourceContents::text("plain", "file:///a.txt"),
+            ],
+        };
+        OutputDir::new(&dir, "resource")
+            .unwrap()
+            .save_read_resource(&mut result)
+            .unwrap();
+
+        let ResourceContents::TextResourceContents {
+            uri,
+            mime_type,
+            text,
+            meta,
+        } = &result.contents[0]
+        else {
+            panic!("blob was not replaced");
+        };
+        let path = dir.canonicalize().unwrap().join("resource-2.pdf");
+        assert_eq!(uri, "file:///report.pdf");
+        assert_eq!(mime_type.as_deref(), Some("text/uri-list"));
+        assert_eq!(text, &file_url(&path));
+        assert_eq!(
+            meta.as_ref().unwrap().get(SAVED_BLOB_META),
+            Some(&json!({ "path": path, "mimeType": "application/pdf", "size": 5 }))
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("resource-1.pdf")).unwrap(),
+            "earlier run"
+        );
+        assert_eq!(
+            result.contents[1],
+            ResourceContents::text("plain", "file:///a.txt")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_call_tool() {
+        let dir = std::env::temp_dir().join(format!("call-mcp-images-{}", std::process::id()));
+        let mut result = CallToolResult::success(vec![
+            Content::image("aGVsbG8=", "image/png"),
+            Content::resource_link(RawResource::new("file:///etc/hosts", "hosts")),
+        ]);
+        OutputDir::new(&dir, "shot")
+            .unwrap()
+            .save_call_tool(&mut result)
+            .unwrap();
+
+        let path = dir.canonicalize().unwrap().join("shot-1.png");
+        let RawContent::ResourceLink(link) = &result.content[0].raw else {
+            panic!("image was not replaced");
+        };
+        assert_eq!(link.uri, file_url(&path));
+        assert_eq!(
+            link.meta.as_ref().unwrap().get(SAVED_BLOB_META),
+            Some(&json!({ "path": path, "mimeType": "image/png", "size": 5 }))
+        );
+        let RawContent::ResourceLink(link) = &result.content[1].raw else {
+            panic!("link was replaced");
+        };
+        assert!(link.meta.is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
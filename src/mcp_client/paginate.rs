@@ -0,0 +1,175 @@
+use crate::errors::AppError;
+use rmcp::model::PaginatedRequestParam;
+use serde_json::Value;
+use std::future::Future;
+
+/// Most pages a single command follows, in case a server keeps returning cursors.
+const MAX_PAGES: usize = 100;
+
+/// Which pages a list command fetches.
+#[derive(Debug, Clone, Default)]
+pub struct Pagination {
+    /// Cursor of the first page.
+    pub cursor: Option<String>,
+    /// Items of the first page to skip, as reported in `next_offset`.
+    pub offset: Option<usize>,
+    /// Follow `next_cursor` until the last page.
+    pub all: bool,
+    /// Follow `next_cursor` until this many items were collected.
+    pub limit: Option<usize>,
+}
+
+/// Items collected from one or more pages.
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor to continue from, when pages remain.
+    pub next_cursor: Option<String>,
+    /// Set when `limit` cut the last page short: the items of that page already returned.
+    /// `next_cursor` is then the cursor of that page, `None` for the first one.
+    pub next_offset: Option<usize>,
+}
+
+/// Adds `truncated: true` and the `next_offset` to resume from to a list result whose last
+/// page `limit` cut short.
+pub(crate) fn mark_truncated(mut result: Value, next_offset: Option<usize>) -> Value {
+    if let Some(offset) = next_offset
+        && let Value::Object(result) = &mut result
+    {
+        result.insert("truncated".to_string(), Value::Bool(true));
+        result.insert("next_offset".to_string(), Value::from(offset));
+    }
+    result
+}
+
+impl Pagination {
+    /// Fetches pages with `fetch` and keeps the items matching `keep`.
+    ///
+    /// Only the first page is fetched unless `all`, `limit` or `follow` is set. Following
+    /// stops after `MAX_PAGES`, returning the cursor of the next page. When `limit` cuts a
+    /// page short, the cursor of that page and `next_offset` resume right after the last
+    /// item returned.
+    pub(crate) async fn collect<T, K, F, Fut>(
+        &self,
+        follow: bool,
+        keep: K,
+        mut fetch: F,
+    ) -> Result<Page<T>, AppError>
+    where
+        K: Fn(&T) -> bool,
+        F: FnMut(Option<PaginatedRequestParam>) -> Fut,
+        Fut: Future<Output = Result<(Vec<T>, Option<String>), AppError>>,
+    {
+        let follow = follow || self.all || self.limit.is_some();
+        let full = |items: &Vec<T>| self.limit.is_some_and(|limit| items.len() >= limit);
+        let mut items = Vec::new();
+        let mut cursor = self.cursor.clone();
+        let mut skip = self.offset.unwrap_or(0);
+        for _ in 0..MAX_PAGES {
+            let page_cursor = cursor.take();
+            let params = page_cursor.clone().map(|cursor| PaginatedRequestParam {
+                cursor: Some(cursor),
+            });
+            let (page, next_cursor) = fetch(params).await?;
+            cursor = next_cursor;
+            for (index, item) in page.into_iter().enumerate().skip(std::mem::take(&mut skip)) {
+                if full(&items) {
+                    return Ok(Page {
+                        items,
+                        next_cursor: page_cursor,
+                        next_offset: Some(index),
+                    });
+                }
+                if keep(&item) {
+                    items.push(item);
+                }
+            }
+            if full(&items) || cursor.is_none() || !follow {
+                break;
+            }
+        }
+        Ok(Page {
+            items,
+            next_cursor: cursor,
+            next_offset: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves `pages` pages of `size` numbered items; cursors are page indexes.
+    async fn collect(
+        pagination: Pagination,
+        pages: usize,
+        size: usize,
+    ) -> (Vec<usize>, Option<String>, Option<usize>) {
+        let mut calls = 0;
+        let page = pagination
+            .collect(
+                false,
+                |_| true,
+                |params| {
+                    calls += 1;
+                    let index: usize = params
+                        .and_then(|params| params.cursor)
+                        .map_or(0, |cursor| cursor.parse().unwrap());
+                    let items = (index * size..(index + 1) * size).collect();
+                    let next = (index + 1 < pages).then(|| (index + 1).to_string());
+                    async move { Ok((items, next)) }
+                },
+            )
+            .await
+            .unwrap();
+        assert!(calls <= MAX_PAGES);
+        (page.items, page.next_cursor, page.next_offset)
+    }
+
+    #[tokio::test]
+    async fn test_collect_pages() {
+        let (items, next, offset) = collect(Pagination::default(), 3, 2).await;
+        assert_eq!(
+            (items, next.as_deref(), offset),
+            (vec![0, 1], Some("1"), None)
+        );
+
+        let all = Pagination {
+            all: true,
+            ..Pagination::default()
+        };
+        let (items, next, _) = collect(all.clone(), 3, 2).await;
+        assert_eq!((items.len(), next), (6, None));
+
+        let (items, next, _) = collect(all, MAX_PAGES + 5, 1).await;
+        assert_eq!(items.len(), MAX_PAGES);
+        assert_eq!(next, Some(MAX_PAGES.to_string()));
+
+        let limit = |limit, cursor: Option<&str>, offset| Pagination {
+            cursor: cursor.map(String::from),
+            offset,
+            limit: Some(limit),
+            ..Pagination::default()
+        };
+        let (items, next, offset) = collect(limit(4, None, None), 3, 2).await;
+        assert_eq!(
+            (items, next.as_deref(), offset),
+            (vec![0, 1, 2, 3], Some("2"), None)
+        );
+        let (items, next, offset) = collect(limit(3, None, None), 3, 2).await;
+        assert_eq!(
+            (items, next.as_deref(), offset),
+            (vec![0, 1, 2], Some("1"), Some(1))
+        );
+        // Resuming continues after the last item returned, on the first page too.
+        let (items, next, offset) = collect(limit(3, Some("1"), Some(1)), 3, 2).await;
+        assert_eq!((items, next, offset), (vec![3, 4, 5], None, None));
+        let (items, next, offset) = collect(limit(1, None, None), 3, 2).await;
+        assert_eq!((items, next, offset), (vec![0], None, Some(1)));
+        let (items, next, offset) = collect(limit(2, None, Some(1)), 3, 2).await;
+        assert_eq!(
+            (items, next.as_deref(), offset),
+            (vec![1, 2], Some("1"), Some(1))
+        );
+    }
+}
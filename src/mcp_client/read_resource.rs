@@ -1,12 +1,19 @@
 use crate::errors::AppError;
-use rmcp::model::ReadResourceRequestParam;
+use rmcp::model::{ReadResourceRequestParam, ReadResourceResult};
+use serde_json::Value;
 
 use super::McpClient;
-use super::text::resource_contents_to_text;
-use super::util::map_service_error;
+use super::uri_template::expand_uri_template;
+use super::util::{map_service_error, value_to_object};
+
+/// Builds a resource URI from an RFC 6570 template and a JSON object of variables.
+pub fn expand_resource_template(template: &str, params: Option<Value>) -> Result<String, AppError> {
+    let variables = value_to_object(params, "params")?.unwrap_or_default();
+    expand_uri_template(template, &variables)
+}
 
 impl McpClient {
-    pub async fn read_resource(&self, uri: String) -> Result<String, AppError> {
+    pub async fn read_resource(&self, uri: String) -> Result<ReadResourceResult, AppError> {
         self.retry("read-resource", || {
             let uri = uri.clone();
             async move {
@@ -17,13 +24,7 @@ impl McpClient {
                     .await
                     .map_err(map_service_error)?;
                 let _ = service.cancel().await;
-                let text = result
-                    .contents
-                    .iter()
-                    .map(resource_contents_to_text)
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                Ok(text)
+                Ok(result)
             }
         })
         .await
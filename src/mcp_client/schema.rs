@@ -0,0 +1,65 @@
+use crate::errors::AppError;
+use rmcp::model::JsonObject;
+use serde_json::{Value, json};
+
+/// Validates `instance` against a tool's JSON Schema.
+///
+/// Fails with `code`; `details.errors` lists each violation with its JSON pointer.
+pub(crate) fn validate_against_schema(
+    schema: &JsonObject,
+    instance: &Value,
+    code: &'static str,
+    subject: &str,
+) -> Result<(), AppError> {
+    let schema = Value::Object(schema.clone());
+    let validator = jsonschema::validator_for(&schema).map_err(|err| {
+        AppError::new("invalid_schema", format!("Invalid {subject} schema: {err}"))
+    })?;
+
+    let errors: Vec<(String, String)> = validator
+        .iter_errors(instance)
+        .map(|err| (err.instance_path().as_str().to_string(), err.to_string()))
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let summary = errors
+        .iter()
+        .map(|(path, message)| {
+            let path = if path.is_empty() { "/" } else { path };
+            format!("{path}: {message}")
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let details: Vec<Value> = errors
+        .into_iter()
+        .map(|(path, message)| json!({ "path": path, "message": message }))
+        .collect();
+    Err(
+        AppError::new(code, format!("{subject} does not match schema: {summary}"))
+            .with_details(json!({ "errors": details })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_against_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "sum": { "type": "number" } },
+            "required": ["sum"]
+        });
+        let schema = schema.as_object().unwrap();
+
+        assert!(validate_against_schema(schema, &json!({"sum": 3}), "bad", "value").is_ok());
+
+        let err =
+            validate_against_schema(schema, &json!({"sum": "3"}), "bad", "value").unwrap_err();
+        assert_eq!(err.code(), "bad");
+        assert_eq!(err.details().unwrap()["errors"][0]["path"], "/sum");
+    }
+}
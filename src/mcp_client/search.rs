@@ -0,0 +1,237 @@
+use crate::errors::AppError;
+use regex::{Regex, RegexBuilder};
+use rmcp::model::Tool;
+use serde_json::Value;
+
+use super::McpClient;
+use super::list_tools::fetch_tools;
+use super::paginate::Pagination;
+use super::util::json_value;
+
+/// How `--name` is compared with item names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MatchMode {
+    /// Exact, case-sensitive name
+    #[default]
+    Exact,
+    /// `*` and `?` wildcards, case-insensitive
+    Glob,
+    /// Regular expression matched anywhere in the name
+    Regex,
+    /// Characters of the pattern appear in order, case-insensitive
+    Fuzzy,
+}
+
+/// Compiled `--name` pattern.
+#[derive(Debug, Clone)]
+pub struct NameFilter {
+    pattern: String,
+    mode: MatchMode,
+    regex: Option<Regex>,
+}
+
+impl NameFilter {
+    pub fn new(pattern: &str, mode: MatchMode) -> Result<Self, AppError> {
+        let source = match mode {
+            MatchMode::Glob => Some(glob_to_regex(pattern)),
+            MatchMode::Regex => Some(pattern.to_string()),
+            MatchMode::Exact | MatchMode::Fuzzy => None,
+        };
+        let regex = source
+            .map(|source| {
+                RegexBuilder::new(&source)
+                    .case_insensitive(mode == MatchMode::Glob)
+                    .build()
+                    .map_err(|err| {
+                        AppError::new(
+                            "invalid_pattern",
+                            format!("Invalid --name pattern '{pattern}': {err}"),
+                        )
+                    })
+            })
+            .transpose()?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            mode,
+            regex,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether only exact lookups are made, which return tools with their output schema.
+    pub fn is_exact(&self) -> bool {
+        self.mode == MatchMode::Exact
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match (&self.regex, self.mode) {
+            (Some(regex), _) => regex.is_match(name),
+            (None, MatchMode::Fuzzy) => is_subsequence(&self.pattern, name),
+            (None, _) => name == self.pattern,
+        }
+    }
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let mut source = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            c => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    source
+}
+
+/// Case-insensitive check that the characters of `pattern` appear in `text` in order.
+fn is_subsequence(pattern: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| text.any(|t| t == c))
+}
+
+#[derive(serde::Serialize)]
+struct ToolMatch {
+    name: String,
+    description: Option<String>,
+    score: u32,
+}
+
+/// Relevance of `tool` for `query`; zero when nothing matches.
+///
+/// Each query term scores highest in the name, then in parameter names, then in the
+/// description. A query that matches no term but is a fuzzy match for the name still
+/// scores a little.
+fn score_tool(tool: &Tool, query: &str) -> u32 {
+    let query = query.to_lowercase();
+    let name = tool.name.to_lowercase();
+    let description = tool
+        .description
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+    let parameters: Vec<String> = tool
+        .input_schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().map(|key| key.to_lowercase()).collect())
+        .unwrap_or_default();
+    let name_words: Vec<&str> = words(&name).collect();
+
+    let mut score = if name == query { 100 } else { 0 };
+    for term in words(&query) {
+        if name_words.contains(&term) {
+            score += 30;
+        } else if name.contains(term) {
+            score += 20;
+        }
+        if parameters.iter().any(|parameter| parameter.contains(term)) {
+            score += 8;
+        }
+        if words(&description).any(|word| word == term) {
+            score += 5;
+        } else if description.contains(term) {
+            score += 2;
+        }
+    }
+    if score == 0 && is_subsequence(&query, &name) {
+        score = 1;
+    }
+    score
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+impl McpClient {
+    /// Ranks every tool against `query` and returns the best `limit` in short form.
+    pub async fn search_tools(&self, query: &str, limit: usize) -> Result<Value, AppError> {
+        self.retry("search-tools", || async {
+            let service = self.connect(false).await?;
+            let all = Pagination {
+                all: true,
+                ..Pagination::default()
+            };
+            let page = all
+                .collect(false, |_| true, |params| fetch_tools(&service, params))
+                .await;
+            let _ = service.cancel().await;
+
+            let mut matches: Vec<ToolMatch> = page?
+                .items
+                .into_iter()
+                .filter_map(|tool| {
+                    let score = score_tool(&tool, query);
+                    (score > 0).then(|| ToolMatch {
+                        name: tool.name.to_string(),
+                        description: tool.description.map(|s| s.to_string()),
+                        score,
+                    })
+                })
+                .collect();
+            matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+            matches.truncate(limit);
+            json_value(serde_json::json!({ "tools": matches }))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_name_filter() {
+        let matches = |pattern, mode, name| NameFilter::new(pattern, mode).unwrap().matches(name);
+        assert!(matches("scrape", MatchMode::Exact, "scrape"));
+        assert!(!matches("scrape", MatchMode::Exact, "scrape_url"));
+        assert!(matches("Scrape_*", MatchMode::Glob, "scrape_url"));
+        assert!(!matches("scrape_?", MatchMode::Glob, "scrape_url"));
+        assert!(matches("^crawl|map$", MatchMode::Regex, "site_map"));
+        assert!(matches("scrl", MatchMode::Fuzzy, "scrape_url"));
+        assert!(!matches("lrs", MatchMode::Fuzzy, "scrape_url"));
+        assert_eq!(
+            NameFilter::new("(", MatchMode::Regex).unwrap_err().code(),
+            "invalid_pattern"
+        );
+    }
+
+    #[test]
+    fn test_score_tool() {
+        let tool = |name: &'static str, description: &'static str, schema: Value| {
+            Tool::new(
+                name,
+                description,
+                Arc::new(schema.as_object().unwrap().clone()),
+            )
+        };
+        let scrape = tool(
+            "scrape_url",
+            "Scrape a single web page",
+            json!({"type": "object", "properties": {"url": {}, "formats": {}}}),
+        );
+        let crawl = tool(
+            "crawl",
+            "Crawl a website starting from a url",
+            json!({"type": "object", "properties": {"url": {}, "limit": {}}}),
+        );
+        let search = tool("search", "Search the web", json!({"type": "object"}));
+
+        assert!(score_tool(&scrape, "scrape page") > score_tool(&crawl, "scrape page"));
+        assert!(score_tool(&crawl, "crawl url") > score_tool(&scrape, "crawl url"));
+        assert_eq!(score_tool(&search, "database"), 0);
+        assert_eq!(score_tool(&search, "srch"), 1);
+    }
+}
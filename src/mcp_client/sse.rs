@@ -0,0 +1,180 @@
+use crate::errors::AppError;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::service::RoleClient;
+use rmcp::transport::Transport;
+use sse_stream::{Sse, SseStream};
+
+use super::core::unauthorized_error;
+
+const EVENT_STREAM_MIME_TYPE: &str = "text/event-stream";
+
+/// Client side of the legacy (2024-11-05) HTTP+SSE transport.
+///
+/// Server messages arrive on a long-lived `GET` event stream; client messages
+/// are `POST`ed to the endpoint announced by the first `endpoint` event.
+pub(crate) struct SseClientTransport {
+    client: reqwest::Client,
+    endpoint: Url,
+    auth_token: Option<String>,
+    events: BoxStream<'static, Result<Sse, sse_stream::Error>>,
+}
+
+impl SseClientTransport {
+    /// Opens the event stream with `stream_client`, which must not have a total request
+    /// timeout since the stream lasts as long as the session, and posts messages with
+    /// `client`.
+    pub(crate) async fn connect(
+        client: reqwest::Client,
+        stream_client: reqwest::Client,
+        url: &str,
+        auth_token: Option<String>,
+    ) -> Result<Self, AppError> {
+        let url = Url::parse(url)
+            .map_err(|err| AppError::new("invalid_url", format!("Invalid URL {url}: {err}")))?;
+
+        let mut request = stream_client
+            .get(url.clone())
+            .header(ACCEPT, EVENT_STREAM_MIME_TYPE);
+        if let Some(token) = &auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|err| {
+            AppError::new("sse_connect", format!("Failed to open SSE stream: {err}"))
+        })?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized_error(url.as_str()));
+        }
+        let response = response.error_for_status().map_err(|err| {
+            AppError::new("sse_connect", format!("Failed to open SSE stream: {err}"))
+        })?;
+
+        let mut events = SseStream::from_bytes_stream(response.bytes_stream()).boxed();
+        let endpoint = read_endpoint(&mut events, &url).await?;
+        Ok(Self {
+            client,
+            endpoint,
+            auth_token,
+            events,
+        })
+    }
+}
+
+/// Waits for the `endpoint` event and resolves its URL against the stream's.
+async fn read_endpoint(
+    events: &mut BoxStream<'static, Result<Sse, sse_stream::Error>>,
+    url: &Url,
+) -> Result<Url, AppError> {
+    while let Some(event) = events.next().await {
+        let event = event
+            .map_err(|err| AppError::new("sse_connect", format!("Invalid SSE stream: {err}")))?;
+        if event.event.as_deref() != Some("endpoint") {
+            continue;
+        }
+
+        let data = event.data.unwrap_or_default();
+        return url.join(data.trim()).map_err(|err| {
+            AppError::new(
+                "sse_connect",
+                format!("Invalid SSE endpoint {}: {err}", data.trim()),
+            )
+        });
+    }
+
+    Err(AppError::new(
+        "sse_connect",
+        "SSE stream closed before an endpoint event was received",
+    ))
+}
+
+impl Transport<RoleClient> for SseClientTransport {
+    type Error = reqwest::Error;
+
+    fn send(
+        &mut self,
+        item: ClientJsonRpcMessage,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let mut request = self.client.post(self.endpoint.clone()).json(&item);
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        async move {
+            request.send().await?.error_for_status()?;
+            Ok(())
+        }
+    }
+
+    /// Returns the next server message. A failed stream ends the session like a closed one,
+    /// and messages that are not valid JSON-RPC are skipped; both are reported on stderr.
+    async fn receive(&mut self) -> Option<ServerJsonRpcMessage> {
+        while let Some(event) = self.events.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    eprintln!("[sse] Event stream failed: {err}");
+                    return None;
+                }
+            };
+            if !matches!(event.event.as_deref(), None | Some("message")) {
+                continue;
+            }
+            let Some(data) = event.data else {
+                continue;
+            };
+            match serde_json::from_str(&data) {
+                Ok(message) => return Some(message),
+                Err(err) => eprintln!("[sse] Ignoring invalid message: {err}"),
+            }
+        }
+        None
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.events = futures::stream::empty().boxed();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::JsonRpcMessage;
+
+    #[tokio::test]
+    async fn test_endpoint_and_messages() {
+        let body: &'static [u8] = b": keep-alive\n\n\
+event: endpoint\ndata: /messages?session=1\n\n\
+event: message\ndata: not json\n\n\
+event: ping\ndata: {}\n\n\
+event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n\
+data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/tools/list_changed\"}\n\n";
+        let chunks = body.chunks(7).map(Ok::<_, std::io::Error>);
+        let mut events = SseStream::from_bytes_stream(futures::stream::iter(chunks)).boxed();
+
+        let url = Url::parse("http://localhost:8080/sse").unwrap();
+        let endpoint = read_endpoint(&mut events, &url).await.unwrap();
+        assert_eq!(
+            endpoint.as_str(),
+            "http://localhost:8080/messages?session=1"
+        );
+
+        let mut transport = SseClientTransport {
+            client: reqwest::Client::new(),
+            endpoint,
+            auth_token: None,
+            events,
+        };
+        let response = transport.receive().await.unwrap();
+        assert!(matches!(response, JsonRpcMessage::Response(_)));
+        let notification = transport.receive().await.unwrap();
+        assert!(matches!(notification, JsonRpcMessage::Notification(_)));
+        assert!(transport.receive().await.is_none());
+
+        let mut closed = futures::stream::empty().boxed();
+        let err = read_endpoint(&mut closed, &url).await.unwrap_err();
+        assert_eq!(err.code(), "sse_connect");
+    }
+}
@@ -1,8 +1,10 @@
 use rmcp::model::{
-    CallToolResult, Content, PromptMessage, PromptMessageContent, PromptMessageRole, RawContent,
-    ResourceContents,
+    CallToolResult, Content, Meta, PromptMessage, PromptMessageContent, PromptMessageRole,
+    RawContent, RawResource, ReadResourceResult, ResourceContents,
 };
 
+use super::output_dir::SAVED_BLOB_META;
+
 pub(crate) fn call_tool_result_to_text(result: &CallToolResult) -> String {
     if !result.content.is_empty() {
         return result
@@ -34,17 +36,41 @@ fn content_to_text(content: &Content) -> String {
             audio.data.len()
         ),
         RawContent::Resource(resource) => resource_contents_to_text(&resource.resource),
-        RawContent::ResourceLink(link) => format!("[resource link uri={}]", link.uri),
+        RawContent::ResourceLink(link) => resource_link_to_text(link),
+    }
+}
+
+/// Images and audio saved with `--output-dir` are shown as the file's path.
+fn resource_link_to_text(link: &RawResource) -> String {
+    match saved_blob_path(link.meta.as_ref()) {
+        Some(path) => path.to_string(),
+        None => format!("[resource link uri={}]", link.uri),
     }
 }
 
+pub(crate) fn read_resource_result_to_text(result: &ReadResourceResult) -> String {
+    result
+        .contents
+        .iter()
+        .map(resource_contents_to_text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub(crate) fn resource_contents_to_text(contents: &ResourceContents) -> String {
     match contents {
-        ResourceContents::TextResourceContents { text, .. } => text.clone(),
+        ResourceContents::TextResourceContents { text, meta, .. } => {
+            saved_blob_path(meta.as_ref()).unwrap_or(text).to_string()
+        }
         ResourceContents::BlobResourceContents { blob, .. } => blob.clone(),
     }
 }
 
+/// Path of content saved with `--output-dir`.
+fn saved_blob_path(meta: Option<&Meta>) -> Option<&str> {
+    meta?.get(SAVED_BLOB_META)?.get("path")?.as_str()
+}
+
 pub(crate) fn prompt_messages_to_text(messages: &[PromptMessage]) -> String {
     messages
         .iter()
@@ -71,6 +97,6 @@ fn prompt_content_to_text(content: &PromptMessageContent) -> String {
         PromptMessageContent::Resource { resource } => {
             resource_contents_to_text(&resource.resource)
         }
-        PromptMessageContent::ResourceLink { link } => format!("[resource link uri={}]", link.uri),
+        PromptMessageContent::ResourceLink { link } => resource_link_to_text(link),
     }
 }
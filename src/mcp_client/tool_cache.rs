@@ -0,0 +1,112 @@
+use crate::auth::{now_unix, write_cache};
+use crate::config::ResolvedConnection;
+use crate::paths::user_cache_dir;
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CACHE_FILE: &str = "tool_cache.json";
+
+/// How long a tool definition is reused before it is fetched again.
+const CACHE_TTL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedTool {
+    tool: Tool,
+    /// Unix timestamp (seconds) after which the definition must be fetched again.
+    expires_at: u64,
+}
+
+/// Tool definitions fetched by earlier runs, so `call-tool` can validate and coerce
+/// arguments without listing the server's tools first.
+pub(crate) struct ToolCache {
+    path: Option<PathBuf>,
+    /// Hash of the server's transport and credentials, so a definition fetched with one
+    /// token is not used with another; the file never stores URLs, commands or tokens.
+    server: String,
+}
+
+impl ToolCache {
+    pub(crate) fn new(connection: &ResolvedConnection) -> Self {
+        // The headers hold the Authorization header of every credential source.
+        let identity = serde_json::to_string(&(&connection.transport, &connection.headers))
+            .unwrap_or_default();
+        Self {
+            path: user_cache_dir().map(|dir| dir.join(CACHE_FILE)),
+            server: Sha256::digest(identity.as_bytes())
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<Tool> {
+        let mut servers = read_cache(self.path.as_deref()?);
+        let cached = servers.remove(&self.server)?.remove(name)?;
+        (cached.expires_at > now_unix()).then_some(cached.tool)
+    }
+
+    pub(crate) fn put(&self, tool: &Tool) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let now = now_unix();
+        let mut servers = read_cache(path);
+        for tools in servers.values_mut() {
+            tools.retain(|_, cached| cached.expires_at > now);
+        }
+        servers.retain(|_, tools| !tools.is_empty());
+        servers.entry(self.server.clone()).or_default().insert(
+            tool.name.to_string(),
+            CachedTool {
+                tool: tool.clone(),
+                expires_at: now + CACHE_TTL_SECS,
+            },
+        );
+        write_cache(path, &servers);
+    }
+}
+
+fn read_cache(path: &Path) -> BTreeMap<String, BTreeMap<String, CachedTool>> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::JsonObject;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_tool_cache() {
+        let path = std::env::temp_dir().join(format!("call-mcp-tools-{}.json", std::process::id()));
+        let cache = |server: &str| ToolCache {
+            path: Some(path.clone()),
+            server: server.to_string(),
+        };
+        let tool = Tool::new("echo", "Echo back text", Arc::new(JsonObject::new()));
+        cache("a").put(&tool);
+        assert_eq!(
+            cache("a").get("echo").map(|tool| tool.name),
+            Some("echo".into())
+        );
+        assert!(cache("a").get("add").is_none());
+        assert!(cache("b").get("echo").is_none());
+
+        let mut servers = read_cache(&path);
+        servers
+            .get_mut("a")
+            .unwrap()
+            .get_mut("echo")
+            .unwrap()
+            .expires_at = now_unix();
+        std::fs::write(&path, serde_json::to_string(&servers).unwrap()).unwrap();
+        assert!(cache("a").get("echo").is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
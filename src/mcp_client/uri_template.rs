@@ -0,0 +1,291 @@
+use crate::errors::AppError;
+use rmcp::model::JsonObject;
+use serde_json::Value;
+
+/// Expansion rules of one RFC 6570 operator.
+struct Operator {
+    first: &'static str,
+    separator: &'static str,
+    named: bool,
+    if_empty: &'static str,
+    allow_reserved: bool,
+}
+
+impl Operator {
+    fn parse(expression: &str) -> (Self, &str) {
+        let operator = |first, separator, named, if_empty, allow_reserved| Operator {
+            first,
+            separator,
+            named,
+            if_empty,
+            allow_reserved,
+        };
+        let Some(first) = expression.chars().next() else {
+            return (operator("", ",", false, "", false), expression);
+        };
+        let rest = &expression[first.len_utf8()..];
+        match first {
+            '+' => (operator("", ",", false, "", true), rest),
+            '#' => (operator("#", ",", false, "", true), rest),
+            '.' => (operator(".", ".", false, "", false), rest),
+            '/' => (operator("/", "/", false, "", false), rest),
+            ';' => (operator(";", ";", true, "", false), rest),
+            '?' => (operator("?", "&", true, "=", false), rest),
+            '&' => (operator("&", "&", true, "=", false), rest),
+            _ => (operator("", ",", false, "", false), expression),
+        }
+    }
+
+    fn encode(&self, value: &str) -> String {
+        encode(value, self.allow_reserved)
+    }
+}
+
+/// Expands an RFC 6570 (level 4) URI template with `variables`.
+///
+/// Strings, numbers and booleans are scalar values, arrays are lists and objects are
+/// associative arrays; missing and `null` variables are undefined.
+pub(crate) fn expand_uri_template(
+    template: &str,
+    variables: &JsonObject,
+) -> Result<String, AppError> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&encode(&rest[..start], true));
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| template_error(template, "unclosed '{'"))?;
+        output.push_str(&expand_expression(
+            template,
+            &rest[start + 1..end],
+            variables,
+        )?);
+        rest = &rest[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(template_error(template, "unmatched '}'"));
+    }
+    output.push_str(&encode(rest, true));
+    Ok(output)
+}
+
+fn expand_expression(
+    template: &str,
+    expression: &str,
+    variables: &JsonObject,
+) -> Result<String, AppError> {
+    let (operator, list) = Operator::parse(expression);
+    let mut parts = Vec::new();
+    for spec in list.split(',') {
+        let (name, explode, prefix) = parse_varspec(template, spec)?;
+        let Some(value) = variables.get(name) else {
+            continue;
+        };
+        if prefix.is_some() && (value.is_array() || value.is_object()) {
+            return Err(template_error(
+                template,
+                &format!("prefix modifier in '{spec}' applies to a list or map value"),
+            ));
+        }
+        if let Some(part) = expand_value(&operator, name, value, explode, prefix) {
+            parts.push(part);
+        }
+    }
+    if parts.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!(
+        "{}{}",
+        operator.first,
+        parts.join(operator.separator)
+    ))
+}
+
+fn parse_varspec<'a>(
+    template: &str,
+    spec: &'a str,
+) -> Result<(&'a str, bool, Option<usize>), AppError> {
+    let (name, explode, prefix) = if let Some(name) = spec.strip_suffix('*') {
+        (name, true, None)
+    } else if let Some((name, length)) = spec.split_once(':') {
+        let length = length
+            .parse::<usize>()
+            .ok()
+            .filter(|length| (1..10_000).contains(length))
+            .ok_or_else(|| template_error(template, &format!("invalid prefix in '{spec}'")))?;
+        (name, false, Some(length))
+    } else {
+        (spec, false, None)
+    };
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '%');
+    if !valid {
+        return Err(template_error(
+            template,
+            &format!("invalid variable name '{spec}'"),
+        ));
+    }
+    Ok((name, explode, prefix))
+}
+
+fn expand_value(
+    operator: &Operator,
+    name: &str,
+    value: &Value,
+    explode: bool,
+    prefix: Option<usize>,
+) -> Option<String> {
+    let named = |encoded: String| {
+        if !operator.named {
+            encoded
+        } else if encoded.is_empty() {
+            format!("{name}{}", operator.if_empty)
+        } else {
+            format!("{name}={encoded}")
+        }
+    };
+
+    match value {
+        Value::Null => None,
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter_map(scalar).collect();
+            if items.is_empty() {
+                return None;
+            }
+            if explode {
+                let items = items.iter().map(|item| named(operator.encode(item)));
+                Some(items.collect::<Vec<_>>().join(operator.separator))
+            } else {
+                let items: Vec<String> = items.iter().map(|item| operator.encode(item)).collect();
+                Some(named(items.join(",")))
+            }
+        }
+        Value::Object(entries) => {
+            let entries: Vec<(String, String)> = entries
+                .iter()
+                .filter_map(|(key, value)| Some((operator.encode(key), scalar(value)?)))
+                .collect();
+            if entries.is_empty() {
+                return None;
+            }
+            if explode {
+                let entries = entries.iter().map(|(key, value)| {
+                    let value = operator.encode(value);
+                    if operator.named && value.is_empty() {
+                        format!("{key}{}", operator.if_empty)
+                    } else {
+                        format!("{key}={value}")
+                    }
+                });
+                Some(entries.collect::<Vec<_>>().join(operator.separator))
+            } else {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .flat_map(|(key, value)| [key.clone(), operator.encode(value)])
+                    .collect();
+                Some(named(entries.join(",")))
+            }
+        }
+        _ => {
+            let value = scalar(value)?;
+            let value = match prefix {
+                Some(length) => value.chars().take(length).collect(),
+                None => value,
+            };
+            Some(named(operator.encode(&value)))
+        }
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Percent-encodes everything except unreserved characters, plus reserved characters and
+/// existing `%XX` triplets when `allow_reserved` is set.
+fn encode(value: &str, allow_reserved: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut output = String::with_capacity(value.len());
+    for (index, &byte) in bytes.iter().enumerate() {
+        let unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+        let reserved = b":/?#[]@!$&'()*+,;=".contains(&byte);
+        let triplet = byte == b'%'
+            && bytes.len() > index + 2
+            && bytes[index + 1].is_ascii_hexdigit()
+            && bytes[index + 2].is_ascii_hexdigit();
+        if unreserved || (allow_reserved && (reserved || triplet)) {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    output
+}
+
+fn template_error(template: &str, reason: &str) -> AppError {
+    AppError::new(
+        "invalid_uri_template",
+        format!("Invalid URI template '{template}': {reason}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_expand_uri_template() {
+        // Examples from RFC 6570 section 3.2.
+        let variables = json!({
+            "var": "value",
+            "hello": "Hello World!",
+            "path": "/foo/bar",
+            "empty": "",
+            "list": ["red", "green", "blue"],
+            "keys": {"semi": ";", "dot": ".", "comma": ","},
+            "x": 1024,
+            "y": 768,
+        });
+        let variables = variables.as_object().unwrap();
+        let cases = [
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("{var:3}", "val"),
+            ("{undef}", ""),
+            ("map?{x,y}", "map?1024,768"),
+            ("{#hello}", "#Hello%20World!"),
+            ("X{.list*}", "X.red.green.blue"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{?list}", "?list=red,green,blue"),
+            ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+            ("{?keys*}", "?semi=%3B&dot=.&comma=%2C"),
+            ("file:///{+path}{.x}", "file:////foo/bar.1024"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(
+                expand_uri_template(template, variables).unwrap(),
+                expected,
+                "{template}"
+            );
+        }
+
+        for template in ["{var", "{é}", "{+é}", "{list:2}", "{keys:1}"] {
+            let err = expand_uri_template(template, variables).unwrap_err();
+            assert_eq!(err.code(), "invalid_uri_template", "{template}");
+        }
+    }
+}
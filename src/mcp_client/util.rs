@@ -1,5 +1,6 @@
 use crate::errors::AppError;
-use rmcp::model::JsonObject;
+use rmcp::model::{ErrorCode, JsonObject};
+use rmcp::service::ServiceError;
 use serde_json::Value;
 
 pub(crate) fn value_to_object(
@@ -24,10 +25,53 @@ pub(crate) fn json_value<T: serde::Serialize>(value: T) -> Result<Value, AppErro
         .map_err(|err| AppError::new("json_encode", format!("Failed to encode result: {err}")))
 }
 
-pub(crate) fn map_service_error(err: rmcp::service::ServiceError) -> AppError {
-    AppError::new("mcp_service", err.to_string())
+/// Maps an error of an MCP request, keeping the category of the server's JSON-RPC error
+/// code where one applies.
+pub(crate) fn map_service_error(err: ServiceError) -> AppError {
+    let code = match &err {
+        ServiceError::McpError(error) => match error.code {
+            ErrorCode::INVALID_PARAMS => "invalid_params",
+            ErrorCode::RESOURCE_NOT_FOUND | ErrorCode::METHOD_NOT_FOUND => "not_found",
+            _ => "mcp_service",
+        },
+        _ => "mcp_service",
+    };
+    AppError::new(code, err.to_string())
 }
 
 pub(crate) fn map_init_error(err: rmcp::service::ClientInitializeError) -> AppError {
     AppError::new("mcp_init", err.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ErrorData;
+
+    #[test]
+    fn test_map_service_error() {
+        let code = |error: ErrorData| map_service_error(ServiceError::McpError(error));
+        assert_eq!(
+            code(ErrorData::invalid_params("bad", None)).code(),
+            "invalid_params"
+        );
+        assert_eq!(
+            code(ErrorData::resource_not_found("gone", None)).code(),
+            "not_found"
+        );
+        assert_eq!(
+            code(ErrorData::new(ErrorCode::METHOD_NOT_FOUND, "nope", None)).code(),
+            "not_found"
+        );
+        let err = code(ErrorData::internal_error("boom", None));
+        assert_eq!(err.code(), "mcp_service");
+        assert_eq!(err.message(), "Mcp error: -32603: boom");
+        assert_eq!(
+            map_service_error(ServiceError::Timeout {
+                timeout: Default::default()
+            })
+            .code(),
+            "mcp_service"
+        );
+    }
+}
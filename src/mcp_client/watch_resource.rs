@@ -0,0 +1,91 @@
+use crate::errors::AppError;
+use rmcp::model::{
+    ReadResourceRequestParam, ReadResourceResult, SubscribeRequestParam, UnsubscribeRequestParam,
+};
+use rmcp::service::{Peer, RoleClient};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use super::McpClient;
+use super::util::map_service_error;
+
+impl McpClient {
+    /// Subscribes to `uri` and passes its contents to `on_contents` once up front and again
+    /// after every `notifications/resources/updated`, until `count` updates were seen or the
+    /// process is interrupted.
+    pub async fn watch_resource<F>(
+        &self,
+        uri: String,
+        count: Option<u64>,
+        mut on_contents: F,
+    ) -> Result<(), AppError>
+    where
+        F: FnMut(ReadResourceResult) -> Result<(), AppError>,
+    {
+        let (sender, mut updates) = mpsc::unbounded_channel();
+        let handler = self.handler(true).with_resource_updates(sender);
+        let service = self
+            .retry("watch-resource", || self.connect_with(handler.clone()))
+            .await?;
+        let result = watch(service.peer(), &uri, count, &mut updates, &mut on_contents).await;
+        let _ = service.cancel().await;
+        result
+    }
+}
+
+async fn watch<F>(
+    peer: &Peer<RoleClient>,
+    uri: &str,
+    count: Option<u64>,
+    updates: &mut UnboundedReceiver<String>,
+    on_contents: &mut F,
+) -> Result<(), AppError>
+where
+    F: FnMut(ReadResourceResult) -> Result<(), AppError>,
+{
+    peer.subscribe(SubscribeRequestParam {
+        uri: uri.to_string(),
+    })
+    .await
+    .map_err(map_service_error)?;
+    on_contents(read(peer, uri).await?)?;
+
+    let interrupt = tokio::signal::ctrl_c();
+    tokio::pin!(interrupt);
+    // The session task ends silently when the server goes away, so poll for it.
+    let mut liveness = tokio::time::interval(Duration::from_secs(1));
+    let mut seen = 0;
+    while count.is_none_or(|count| seen < count) {
+        tokio::select! {
+            update = updates.recv() => {
+                let Some(updated) = update else { break };
+                seen += 1;
+                on_contents(read(peer, &updated).await?)?;
+            }
+            _ = &mut interrupt => break,
+            _ = liveness.tick() => {
+                if peer.is_transport_closed() {
+                    return Err(AppError::new(
+                        "connection_closed",
+                        format!("Server closed the session while watching {uri}"),
+                    ));
+                }
+            }
+        }
+    }
+
+    let _ = peer
+        .unsubscribe(UnsubscribeRequestParam {
+            uri: uri.to_string(),
+        })
+        .await;
+    Ok(())
+}
+
+async fn read(peer: &Peer<RoleClient>, uri: &str) -> Result<ReadResourceResult, AppError> {
+    peer.read_resource(ReadResourceRequestParam {
+        uri: uri.to_string(),
+    })
+    .await
+    .map_err(map_service_error)
+}
@@ -1,11 +1,24 @@
 use crate::errors::AppError;
 use serde_json::{Value, json};
 
-pub fn print_ok(result: Value) {
-    print_yaml(&result);
+/// How results and errors are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Plain text for tool/resource/prompt content, YAML for everything else
+    #[default]
+    Text,
+    Yaml,
+    /// Pretty-printed JSON
+    Json,
+    /// One compact JSON document per line
+    Ndjson,
 }
 
-pub fn print_error(error: &AppError) {
+pub fn print_ok(result: &Value, format: OutputFormat) {
+    print_value(result, format);
+}
+
+pub fn print_error(error: &AppError, format: OutputFormat) {
     let mut payload = json!({
         "code": error.code(),
         "message": error.message(),
@@ -15,7 +28,28 @@ pub fn print_error(error: &AppError) {
         payload["details"] = details.clone();
     }
 
-    print_yaml(&payload);
+    print_value(&payload, format);
+}
+
+fn print_value(value: &Value, format: OutputFormat) {
+    match format {
+        OutputFormat::Text | OutputFormat::Yaml => print_yaml(value),
+        OutputFormat::Json => match serde_json::to_string_pretty(value) {
+            Ok(json) => println!("{json}"),
+            Err(err) => print_encode_error(&err),
+        },
+        OutputFormat::Ndjson => match serde_json::to_string(value) {
+            Ok(json) => println!("{json}"),
+            Err(err) => print_encode_error(&err),
+        },
+    }
+}
+
+fn print_encode_error(err: &serde_json::Error) {
+    println!(
+        "{}",
+        json!({ "code": "json_encode", "message": err.to_string() })
+    );
 }
 
 fn print_yaml(value: &Value) {
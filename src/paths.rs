@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+/// Returns the per-user configuration directory for call-mcp.
+///
+/// Uses `%APPDATA%\call-mcp` on Windows, otherwise `$XDG_CONFIG_HOME/call-mcp`
+/// falling back to `~/.config/call-mcp`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    base.map(|base| base.join("call-mcp"))
+}
+
+/// Returns the per-user cache directory for call-mcp.
+///
+/// Uses `%LOCALAPPDATA%\call-mcp` on Windows, otherwise `$XDG_CACHE_HOME/call-mcp`
+/// falling back to `~/.cache/call-mcp`.
+pub fn user_cache_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    };
+
+    base.map(|base| base.join("call-mcp"))
+}
+
+/// Returns the current user's home directory (`%USERPROFILE%` on Windows, `$HOME` elsewhere).
+pub fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns the Unix socket `call-mcp daemon` listens on: `$XDG_RUNTIME_DIR/call-mcp.sock`,
+/// falling back to `daemon.sock` in the user config directory.
+pub fn daemon_socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|value| !value.is_empty())
+        .map(|dir| PathBuf::from(dir).join("call-mcp.sock"))
+        .or_else(|| user_config_dir().map(|dir| dir.join("daemon.sock")))
+}
@@ -2,7 +2,7 @@ use std::fmt;
 use std::str::FromStr;
 
 /// Predefined User-Agent presets.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
 pub enum UserAgentPreset {
     /// Google Chrome browser (default)
     #[default]